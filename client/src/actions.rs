@@ -1,22 +1,79 @@
-use bevy::{math::Vec2, reflect::Reflect};
-use leafwing_input_manager::{action_state::ActionState, Actionlike};
+use bevy::{
+    input::{gamepad::GamepadButtonType, mouse::MouseButton},
+    math::Vec2,
+    reflect::Reflect,
+};
+use leafwing_input_manager::{
+    action_state::ActionState,
+    axislike::{DeadZoneShape, DualAxis, VirtualDPad},
+    input_map::InputMap,
+    user_input::InputKind,
+    Actionlike,
+};
 
-use super::bool_to_f32;
+use crate::keybindings::{Binding, Keybindings, ALL_BINDINGS};
+
+/// How far the stick has to be pushed before it moves the player, anything inside is drift.
+pub const STICK_DEADZONE: f32 = 0.15;
 
 #[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect)]
 pub enum GameActions {
-    W,
-    A,
-    S,
-    D,
+    /// A dual-axis action, see `get_input_vector`.
+    Move,
+    Pickup,
+    Inventory,
+    CycleColor,
+    CycleSkin,
+    Leaderboard,
+    /// Walks to the clicked spot, see `player_plugin::click_to_move`.
+    MoveTo,
 }
 
-pub fn get_input_vector(action_state: &ActionState<GameActions>) -> Vec2 {
-    Vec2 {
-        x: bool_to_f32(action_state.pressed(GameActions::D))
-            - bool_to_f32(action_state.pressed(GameActions::A)),
-
-        y: bool_to_f32(action_state.pressed(GameActions::W))
-            - bool_to_f32(action_state.pressed(GameActions::S)),
+/// The keyboard bindings from `keybindings` plus the mouse and gamepad bindings, which can't
+/// be rebound.
+/// The keyboard and any gamepad can be used at the same time.
+pub fn input_map(keybindings: &Keybindings) -> InputMap<GameActions> {
+    let mut input_map = InputMap::default();
+    for binding in ALL_BINDINGS {
+        if let Some(action) = binding.action() {
+            input_map.insert(keybindings.key(binding), action);
+        }
     }
+
+    let key = |binding| InputKind::Keyboard(keybindings.key(binding));
+    input_map
+        .insert(
+            VirtualDPad {
+                up: key(Binding::MoveUp),
+                down: key(Binding::MoveDown),
+                left: key(Binding::MoveLeft),
+                right: key(Binding::MoveRight),
+            },
+            GameActions::Move,
+        )
+        .insert(VirtualDPad::dpad(), GameActions::Move)
+        .insert(
+            DualAxis::left_stick().with_deadzone(DeadZoneShape::Ellipse {
+                radius_x: STICK_DEADZONE,
+                radius_y: STICK_DEADZONE,
+            }),
+            GameActions::Move,
+        )
+        .insert(GamepadButtonType::South, GameActions::Pickup)
+        .insert(GamepadButtonType::North, GameActions::Inventory)
+        .insert(GamepadButtonType::West, GameActions::CycleColor)
+        .insert(GamepadButtonType::East, GameActions::CycleSkin)
+        .insert(GamepadButtonType::Select, GameActions::Leaderboard)
+        .insert(MouseButton::Right, GameActions::MoveTo);
+    input_map
+}
+
+/// The direction to move in, at most 1 long. Keys and the D-pad always give a full step,
+/// while the stick keeps how far it's pushed so players can walk slower.
+pub fn get_input_vector(action_state: &ActionState<GameActions>) -> Vec2 {
+    action_state
+        .axis_pair(GameActions::Move)
+        .map(|axis| axis.xy())
+        .unwrap_or_default()
+        .clamp_length_max(1.0)
 }
@@ -0,0 +1,43 @@
+use bevy::{
+    asset::{AssetServer, Handle},
+    render::{color::Color, texture::Image},
+};
+
+/// Every color the server accepts in `set_appearance`, this should match `ALLOWED_COLORS` on the server.
+pub const ALLOWED_COLORS: [&str; 8] = [
+    "white", "red", "orange", "yellow", "green", "blue", "purple", "pink",
+];
+/// Every skin the server accepts in `set_appearance`, this should match `ALLOWED_SKINS` on the server.
+pub const ALLOWED_SKINS: [&str; 4] = ["default", "knight", "mage", "rogue"];
+
+pub fn appearance_color(color: &str) -> Color {
+    match color {
+        "red" => Color::RED,
+        "orange" => Color::ORANGE,
+        "yellow" => Color::YELLOW,
+        "green" => Color::GREEN,
+        "blue" => Color::BLUE,
+        "purple" => Color::PURPLE,
+        "pink" => Color::PINK,
+        _ => Color::WHITE,
+    }
+}
+
+/// The `default` skin, or any skin we don't know about, is drawn without a texture.
+pub fn skin_texture(skin: &str, assets: &AssetServer) -> Handle<Image> {
+    match skin {
+        "knight" => assets.load("skins/knight.png"),
+        "mage" => assets.load("skins/mage.png"),
+        "rogue" => assets.load("skins/rogue.png"),
+        _ => Default::default(),
+    }
+}
+
+/// Returns the option after `current` in `allowed`, wrapping around to the first.
+pub fn next_allowed(current: &str, allowed: &[&str]) -> String {
+    let index = allowed
+        .iter()
+        .position(|option| *option == current)
+        .map_or(0, |index| (index + 1) % allowed.len());
+    allowed[index].to_string()
+}
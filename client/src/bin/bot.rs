@@ -0,0 +1,331 @@
+//! Headless bots for load and soak testing the module on a local SpacetimeDB instance.
+//!
+//! `cargo run --bin bot -- [bots] [seconds]` starts one process per bot, since the SDK only
+//! holds a single connection per process. Every bot joins with a new identity, walks a
+//! scripted pattern with `update_player_pos`, and the combined report is printed at the end.
+
+use std::{
+    collections::VecDeque,
+    f32::consts::TAU,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    app::{App, AppExit, ScheduleRunnerPlugin, Update},
+    ecs::{
+        event::{EventReader, EventWriter},
+        system::{Res, ResMut, Resource},
+    },
+    time::{Time, Timer, TimerMode},
+    MinimalPlugins,
+};
+use client::{
+    connect_to_db, is_local,
+    module_bindings::{create_player, update_player_pos, StdbVector2},
+    register_callbacks,
+    room::LOBBY_ROOM_ID,
+    subscribe_to,
+    team::NO_TEAM,
+    uncb_receiver::{self, process_messages, UncbEvent, UncbMessage},
+};
+
+const DEFAULT_BOTS: usize = 10;
+const DEFAULT_SECS: u64 = 60;
+/// How often every bot runs its systems, bots don't need to run any faster than a real client.
+const BOT_FRAME_SECS: f64 = 1.0 / 60.0;
+/// How often every bot sends its position.
+const MOVE_INTERVAL_SECS: f32 = 0.05;
+/// Bots print their results on a line starting with this for the parent process to collect.
+const REPORT_PREFIX: &str = "bot-report";
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.get(1).map(String::as_str) == Some("--bot") {
+        let index = parse_arg(args.get(2), 0);
+        let secs = parse_arg(args.get(3), DEFAULT_SECS);
+        run_bot(index, secs);
+    } else {
+        let bots = parse_arg(args.get(1), DEFAULT_BOTS);
+        let secs = parse_arg(args.get(2), DEFAULT_SECS);
+        run_bots(bots, secs);
+    }
+}
+
+fn parse_arg<T: std::str::FromStr>(arg: Option<&String>, default: T) -> T {
+    arg.and_then(|arg| arg.parse().ok()).unwrap_or(default)
+}
+
+//#region parent
+/// Starts `bots` copies of this binary in bot mode and reports on them once they all finish.
+fn run_bots(bots: usize, secs: u64) {
+    println!("Starting {} bots for {}s", bots, secs);
+    let exe = std::env::current_exe().expect("Failed to find the bot binary");
+
+    let children: Vec<_> = (0..bots)
+        .map(|index| {
+            Command::new(&exe)
+                .args(["--bot", &index.to_string(), &secs.to_string()])
+                .stdout(Stdio::piped())
+                .spawn()
+                .expect("Failed to start a bot")
+        })
+        .collect();
+
+    let mut reports = Vec::new();
+    for child in children {
+        let output = child.wait_with_output().expect("Failed to wait for a bot");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if let Some(report) = stdout.lines().find_map(BotReport::parse) {
+            reports.push(report);
+        }
+    }
+
+    print_report(bots, secs, &reports);
+}
+
+fn print_report(bots: usize, secs: u64, reports: &[BotReport]) {
+    let sent: u64 = reports.iter().map(|report| report.sent).sum();
+    let rows: u64 = reports.iter().map(|report| report.rows).sum();
+    let mut latencies: Vec<u64> = reports
+        .iter()
+        .flat_map(|report| report.latencies_micros.iter().copied())
+        .collect();
+    latencies.sort_unstable();
+
+    println!("Bots finished: {} of {}", reports.len(), bots);
+    println!("Moves: {} sent, {} acknowledged", sent, latencies.len());
+    if !latencies.is_empty() {
+        let mean = latencies.iter().sum::<u64>() / latencies.len() as u64;
+        println!(
+            "Reducer latency: mean {}  p50 {}  p95 {}  p99 {}  max {}",
+            format_micros(mean),
+            format_micros(percentile(&latencies, 0.50)),
+            format_micros(percentile(&latencies, 0.95)),
+            format_micros(percentile(&latencies, 0.99)),
+            format_micros(*latencies.last().unwrap()),
+        );
+    }
+    if !reports.is_empty() {
+        let per_sec = rows as f64 / secs.max(1) as f64;
+        println!(
+            "Row updates received: {} ({:.0}/s total, {:.0}/s per bot)",
+            rows,
+            per_sec,
+            per_sec / reports.len() as f64
+        );
+    }
+}
+
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[index]
+}
+
+fn format_micros(micros: u64) -> String {
+    format!("{:.1}ms", micros as f64 / 1000.0)
+}
+//#endregion parent
+
+//#region bot
+/// What a single bot measured, sent to the parent as one line of stdout.
+struct BotReport {
+    sent: u64,
+    rows: u64,
+    latencies_micros: Vec<u64>,
+}
+
+impl BotReport {
+    fn line(&self) -> String {
+        let latencies: Vec<String> = self
+            .latencies_micros
+            .iter()
+            .map(|latency| latency.to_string())
+            .collect();
+        format!(
+            "{} {} {} {}",
+            REPORT_PREFIX,
+            self.sent,
+            self.rows,
+            latencies.join(",")
+        )
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.strip_prefix(REPORT_PREFIX)?.split_whitespace();
+        Some(Self {
+            sent: parts.next()?.parse().ok()?,
+            rows: parts.next()?.parse().ok()?,
+            latencies_micros: parts
+                .next()
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|latency| latency.parse().ok())
+                .collect(),
+        })
+    }
+}
+
+/// The scripted movement of a bot, picked from its index so a run has a mix of all of them.
+#[derive(Clone, Copy)]
+enum Pattern {
+    Circle,
+    Patrol,
+    Wander,
+}
+
+impl Pattern {
+    /// Where a bot centered on `center` is `t` seconds into its run, always inside the world.
+    fn position(self, center: StdbVector2, t: f32) -> StdbVector2 {
+        let (x, y) = match self {
+            Pattern::Circle => (150.0 * t.cos(), 150.0 * t.sin()),
+            Pattern::Patrol => (300.0 * (t * 0.8).sin(), 0.0),
+            Pattern::Wander => (
+                120.0 * (t * 0.7).sin() + 60.0 * (t * 1.9).cos(),
+                100.0 * (t * 0.5).cos() + 50.0 * (t * 2.3).sin(),
+            ),
+        };
+        StdbVector2 {
+            x: center.x + x,
+            y: center.y + y,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Bot {
+    pattern: Pattern,
+    center: StdbVector2,
+    /// Offsets the patterns so bots with the same one don't move in lockstep.
+    phase: f32,
+    deadline: Instant,
+    /// Our player's object, we can't move until `create_player` has gone through.
+    object_id: Option<u64>,
+    move_timer: Timer,
+    /// Positions sent with `update_player_pos` that haven't come back as an update yet.
+    pending: VecDeque<(StdbVector2, Instant)>,
+    report: BotReport,
+}
+
+impl Bot {
+    fn new(index: usize, secs: u64) -> Self {
+        Self {
+            pattern: match index % 3 {
+                0 => Pattern::Circle,
+                1 => Pattern::Patrol,
+                _ => Pattern::Wander,
+            },
+            center: StdbVector2 {
+                x: -600.0 + (index % 7) as f32 * 200.0,
+                y: -400.0 + (index / 7 % 5) as f32 * 200.0,
+            },
+            phase: index as f32 * TAU / 7.0,
+            deadline: Instant::now() + Duration::from_secs(secs),
+            object_id: None,
+            move_timer: Timer::from_seconds(MOVE_INTERVAL_SECS, TimerMode::Repeating),
+            pending: VecDeque::new(),
+            report: BotReport {
+                sent: 0,
+                rows: 0,
+                latencies_micros: Vec::new(),
+            },
+        }
+    }
+}
+
+/// Runs a single bot, reusing the client's connection and callbacks without a window.
+fn run_bot(index: usize, secs: u64) {
+    let (uncb_send, uncb_receiver) = uncb_receiver::channel();
+    register_callbacks(uncb_send);
+    // Every bot gets a new identity, so they're never given the saved credentials.
+    connect_to_db(None);
+    subscribe_to(LOBBY_ROOM_ID, NO_TEAM);
+
+    let mut app = App::new();
+    app.insert_resource(uncb_receiver)
+        .insert_resource(Bot::new(index, secs))
+        .add_event::<UncbEvent>()
+        .add_plugins(
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+                BOT_FRAME_SECS,
+            ))),
+        )
+        .add_systems(
+            Update,
+            (process_messages, handle_messages, move_bot, finish_bot),
+        );
+    // `App::run` takes the world with it, so `finish_bot` prints the report.
+    app.run();
+}
+
+/// Creates the bot's player once connected, and measures the time from sending each position
+/// to receiving the update of our object with it. Every row message counts towards throughput.
+fn handle_messages(mut bot: ResMut<Bot>, mut er: EventReader<UncbEvent>) {
+    for ev in er.read() {
+        match &ev.message {
+            UncbMessage::Connected { .. } => create_player(),
+            UncbMessage::PlayerInserted { data, event: _ } => {
+                if is_local(&data.client_id) {
+                    bot.object_id = Some(data.object_id);
+                }
+            }
+            UncbMessage::ObjectUpdated {
+                old: _,
+                new,
+                event: _,
+            } => {
+                if bot.object_id == Some(new.object_id) {
+                    // Positions that never came back, e.g. because the server clamped them,
+                    // are dropped along the way.
+                    while let Some((position, sent_at)) = bot.pending.pop_front() {
+                        if position.x == new.position.x && position.y == new.position.y {
+                            let latency = sent_at.elapsed().as_micros() as u64;
+                            bot.report.latencies_micros.push(latency);
+                            break;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        match &ev.message {
+            UncbMessage::PlayerInserted { .. }
+            | UncbMessage::PlayerUpdated { .. }
+            | UncbMessage::PlayerRemoved { .. }
+            | UncbMessage::ObjectInserted { .. }
+            | UncbMessage::ObjectUpdated { .. }
+            | UncbMessage::ObjectRemoved { .. }
+            | UncbMessage::InventoryItemInserted { .. }
+            | UncbMessage::InventoryItemUpdated { .. }
+            | UncbMessage::InventoryItemRemoved { .. }
+            | UncbMessage::ChatMessageInserted { .. }
+            | UncbMessage::ZoneEventInserted { .. }
+            | UncbMessage::PortalJumped { .. } => bot.report.rows += 1,
+            _ => {}
+        }
+    }
+}
+
+/// Sends the next position of the bot's pattern every `MOVE_INTERVAL_SECS`.
+fn move_bot(time: Res<Time>, mut bot: ResMut<Bot>) {
+    if bot.object_id.is_none() || !bot.move_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let t = time.elapsed_seconds() + bot.phase;
+    let position = bot.pattern.position(bot.center.clone(), t);
+    update_player_pos(position.clone());
+    bot.pending.push_back((position, Instant::now()));
+    bot.report.sent += 1;
+}
+
+/// Prints the report for `run_bots` to collect once the bot's time is up, then exits.
+fn finish_bot(bot: Res<Bot>, mut exit: EventWriter<AppExit>) {
+    if Instant::now() >= bot.deadline {
+        println!("{}", bot.report.line());
+        exit.send(AppExit);
+    }
+}
+//#endregion bot
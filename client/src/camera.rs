@@ -0,0 +1,11 @@
+use bevy::ecs::component::Component;
+
+/// How quickly the camera catches up to the local player, higher is snappier.
+pub const CAMERA_FOLLOW_SPEED: f32 = 6.0;
+/// How much a single scroll of the mouse wheel changes the zoom.
+pub const CAMERA_ZOOM_STEP: f32 = 0.1;
+pub const CAMERA_MIN_ZOOM: f32 = 0.5;
+pub const CAMERA_MAX_ZOOM: f32 = 2.5;
+
+#[derive(Component)]
+pub struct MainCamera;
@@ -0,0 +1,104 @@
+use bevy::{
+    app::{App, Plugin, PostUpdate, Startup, Update},
+    core_pipeline::core_2d::Camera2dBundle,
+    ecs::{
+        event::EventReader,
+        query::{With, Without},
+        schedule::IntoSystemConfigs,
+        system::{Commands, Query, Res},
+    },
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    math::Vec2,
+    render::camera::OrthographicProjection,
+    time::Time,
+    transform::{components::Transform, TransformSystem},
+};
+use leafwing_input_manager::action_state::ActionState;
+
+use crate::{
+    actions::GameActions,
+    camera::{MainCamera, CAMERA_FOLLOW_SPEED, CAMERA_MAX_ZOOM, CAMERA_MIN_ZOOM, CAMERA_ZOOM_STEP},
+    world::world_bounds,
+};
+
+pub struct CameraPlugin;
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (init_camera,))
+            .add_systems(Update, (zoom_camera,))
+            .add_systems(
+                PostUpdate,
+                (follow_player,).before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+fn init_camera(mut c: Commands) {
+    c.spawn((
+        MainCamera,
+        Camera2dBundle {
+            ..Default::default()
+        },
+    ));
+}
+
+/// Eases the camera towards the entity carrying the `ActionState<GameActions>`, then keeps
+/// the visible area inside the world bounds published by the server.
+fn follow_player(
+    time: Res<Time>,
+    q_player: Query<&Transform, With<ActionState<GameActions>>>,
+    mut q_camera: Query<
+        (&mut Transform, &OrthographicProjection),
+        (With<MainCamera>, Without<ActionState<GameActions>>),
+    >,
+) {
+    let Ok(player_transform) = q_player.get_single() else {
+        return;
+    };
+
+    for (mut transform, projection) in &mut q_camera {
+        let target = player_transform.translation.truncate();
+        let t = 1.0 - (-CAMERA_FOLLOW_SPEED * time.delta_seconds()).exp();
+        let mut position = transform.translation.truncate().lerp(target, t);
+
+        if let Some(bounds) = world_bounds() {
+            let half_view = projection.area.half_size();
+            position = Vec2 {
+                x: clamp_axis(position.x, bounds.min.x, bounds.max.x, half_view.x),
+                y: clamp_axis(position.y, bounds.min.y, bounds.max.y, half_view.y),
+            };
+        }
+
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+/// Scrolling the mouse wheel up zooms in, down zooms out.
+fn zoom_camera(
+    mut q: Query<&mut OrthographicProjection, With<MainCamera>>,
+    mut er: EventReader<MouseWheel>,
+) {
+    for ev in er.read() {
+        // Pixel scrolling (touchpads) sends far larger values than a wheel's lines.
+        let scroll = match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            MouseScrollUnit::Pixel => ev.y / 100.0,
+        };
+
+        for mut projection in &mut q {
+            projection.scale = (projection.scale * (1.0 - scroll * CAMERA_ZOOM_STEP))
+                .clamp(CAMERA_MIN_ZOOM, CAMERA_MAX_ZOOM);
+        }
+    }
+}
+
+/// Keeps a camera centered at `v` with a view `half_view` wide on both sides between
+/// `min` and `max`, centering it when the view is larger than the world.
+fn clamp_axis(v: f32, min: f32, max: f32, half_view: f32) -> f32 {
+    if max - min <= half_view * 2.0 {
+        (min + max) / 2.0
+    } else {
+        v.clamp(min + half_view, max - half_view)
+    }
+}
@@ -0,0 +1,21 @@
+use bevy::ecs::{component::Component, system::Resource};
+
+/// Longest message the server accepts, this should match `CHAT_MESSAGE_MAX_LEN` on the server.
+pub const CHAT_MESSAGE_MAX_LEN: usize = 200;
+/// `recipient_id` of chat messages sent to everyone, this should match `everyone()` on the server.
+pub const EVERYONE: [u8; 32] = [0; 32];
+/// How many of the latest messages are shown in the chat log.
+pub const CHAT_LOG_LINES: usize = 8;
+
+#[derive(Component)]
+pub struct ChatLog;
+
+#[derive(Component)]
+pub struct ChatInputText;
+
+/// The message being typed. While `active` the game actions are disabled so typing doesn't move the player.
+#[derive(Resource, Default)]
+pub struct ChatInput {
+    pub active: bool,
+    pub text: String,
+}
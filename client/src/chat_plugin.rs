@@ -0,0 +1,180 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    ecs::{
+        event::EventReader,
+        query::With,
+        system::{Commands, Query, Res, ResMut},
+    },
+    hierarchy::BuildChildren,
+    input::{keyboard::KeyCode, Input},
+    render::color::Color,
+    text::{Text, TextStyle},
+    ui::{
+        node_bundles::{NodeBundle, TextBundle},
+        FlexDirection, PositionType, Style, UiRect, Val,
+    },
+    window::ReceivedCharacter,
+};
+use leafwing_input_manager::plugin::ToggleActions;
+use spacetimedb_sdk::table::TableType;
+
+use crate::{
+    actions::GameActions,
+    chat::{ChatInput, ChatInputText, ChatLog, CHAT_LOG_LINES, CHAT_MESSAGE_MAX_LEN},
+    commands::run_command,
+    diagnostics::{NetworkStats, SentCounter},
+    identity_leading_hex, send_chat_message,
+    team::NO_TEAM,
+    uncb_receiver::{UncbEvent, UncbMessage},
+    StdbChatMessage,
+};
+
+pub struct ChatPlugin;
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChatInput>()
+            .add_systems(Startup, (init_chat,))
+            .add_systems(Update, (refresh_chat_log, type_chat));
+    }
+}
+
+/// Spawns the chat log and the input line in the bottom left corner of the screen.
+fn init_chat(mut c: Commands) {
+    let style = TextStyle {
+        font_size: 18.0,
+        color: Color::WHITE,
+        ..Default::default()
+    };
+
+    c.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            flex_direction: FlexDirection::Column,
+            ..Default::default()
+        },
+        background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+        ..Default::default()
+    })
+    .with_children(|parent| {
+        parent.spawn((ChatLog, TextBundle::from_section("", style.clone())));
+        parent.spawn((
+            ChatInputText,
+            TextBundle::from_section(input_text(&ChatInput::default()), style),
+        ));
+    });
+}
+
+/// Listens for the `UncbMessage::ChatMessageInserted` and `UncbMessage::SubscriptionApplied`
+/// messages and rebuilds the chat log from the latest messages.
+fn refresh_chat_log(mut q: Query<&mut Text, With<ChatLog>>, mut er: EventReader<UncbEvent>) {
+    let mut changed = false;
+
+    for ev in er.read() {
+        match &ev.message {
+            UncbMessage::ChatMessageInserted { .. } | UncbMessage::SubscriptionApplied => {
+                changed = true;
+            }
+            _ => {}
+        }
+    }
+
+    if changed {
+        for mut text in &mut q {
+            text.sections[0].value = chat_log_text();
+        }
+    }
+}
+
+/// Enter opens the input line, typing fills it and Enter again sends it. Messages starting
+/// with `/` are run as commands instead. Escape closes the input line without sending.
+fn type_chat(
+    keys: Res<Input<KeyCode>>,
+    mut chars: EventReader<ReceivedCharacter>,
+    mut input: ResMut<ChatInput>,
+    mut toggle_actions: ResMut<ToggleActions<GameActions>>,
+    mut q: Query<&mut Text, With<ChatInputText>>,
+    stats: Res<NetworkStats>,
+) {
+    if !input.active {
+        chars.clear();
+        if !keys.just_pressed(KeyCode::Return) {
+            return;
+        }
+        input.active = true;
+        toggle_actions.enabled = false;
+    } else {
+        for ev in chars.read() {
+            if !ev.char.is_control() && input.text.chars().count() < CHAT_MESSAGE_MAX_LEN {
+                input.text.push(ev.char);
+            }
+        }
+        if keys.just_pressed(KeyCode::Back) {
+            input.text.pop();
+        }
+
+        if keys.just_pressed(KeyCode::Return) {
+            let text = std::mem::take(&mut input.text);
+            submit_chat(text.trim(), &stats.sent);
+            input.active = false;
+        } else if keys.just_pressed(KeyCode::Escape) {
+            input.text.clear();
+            input.active = false;
+        }
+        toggle_actions.enabled = !input.active;
+    }
+
+    for mut text in &mut q {
+        text.sections[0].value = input_text(&input);
+    }
+}
+
+fn submit_chat(text: &str, sent: &SentCounter) {
+    if text.is_empty() {
+        return;
+    }
+
+    if text.starts_with('/') {
+        if let Err(e) = run_command(text, sent) {
+            eprintln!("{}", e);
+        }
+    } else {
+        sent.count_reducer_call();
+        send_chat_message(text.to_string());
+    }
+}
+
+fn input_text(input: &ChatInput) -> String {
+    if input.active {
+        format!("> {}_", input.text)
+    } else {
+        "Press Enter to chat".to_string()
+    }
+}
+
+fn chat_log_text() -> String {
+    let mut messages: Vec<StdbChatMessage> = StdbChatMessage::iter().collect();
+    messages.sort_by_key(|message| message.message_id);
+
+    let start = messages.len().saturating_sub(CHAT_LOG_LINES);
+    messages[start..]
+        .iter()
+        .map(|message| {
+            // Team messages are only delivered to our own team.
+            let scope = if message.team_id == NO_TEAM {
+                ""
+            } else {
+                "(team) "
+            };
+            format!(
+                "{}[{}] {}",
+                scope,
+                identity_leading_hex(&message.sender_id),
+                message.text
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,133 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bevy::{
+    ecs::system::Resource,
+    time::{Timer, TimerMode},
+};
+
+/// How often the server's clock is sampled with `sync_clock`.
+pub const CLOCK_SYNC_SECS: f32 = 2.0;
+/// How much of each new sample is mixed into the offset and round trip of `ServerClock`.
+pub const CLOCK_SMOOTHING: f64 = 0.2;
+
+/// The server's clock as seen from ours, estimated from the answers to `sync_clock`. Times
+/// are microseconds since the unix epoch, like `local_micros`.
+#[derive(Resource, Default, Debug)]
+pub struct ServerClock {
+    /// Smoothed server time minus local time, `None` until the first sample.
+    offset_micros: Option<f64>,
+    /// Smoothed time from calling `sync_clock` to seeing its answer.
+    rtt_micros: Option<f64>,
+    pub samples: u64,
+}
+
+impl ServerClock {
+    /// Mixes in the answer to a `sync_clock` call sent at `sent` and seen at `received`,
+    /// assuming the server ran it halfway through the round trip.
+    pub fn add_sample(&mut self, sent: u64, server: u64, received: u64) {
+        // Our clock went backwards in between, the sample says nothing.
+        if received < sent {
+            return;
+        }
+
+        let rtt = (received - sent) as f64;
+        let offset = server as f64 - (sent as f64 + rtt / 2.0);
+        self.rtt_micros = Some(smooth(self.rtt_micros, rtt));
+        self.offset_micros = Some(smooth(self.offset_micros, offset));
+        self.samples += 1;
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.offset_micros.is_some()
+    }
+
+    pub fn offset_micros(&self) -> Option<i64> {
+        self.offset_micros.map(|offset| offset.round() as i64)
+    }
+
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt_micros
+            .map(|rtt| Duration::from_micros(rtt.round() as u64))
+    }
+
+    /// The server time at local time `local`.
+    pub fn to_server(&self, local: u64) -> Option<u64> {
+        Some(local.saturating_add_signed(self.offset_micros()?))
+    }
+
+    /// The local time at server time `server`.
+    pub fn to_local(&self, server: u64) -> Option<u64> {
+        Some(server.saturating_add_signed(-self.offset_micros()?))
+    }
+
+    /// The server's clock right now.
+    pub fn server_now(&self) -> Option<u64> {
+        self.to_server(local_micros())
+    }
+}
+
+fn smooth(current: Option<f64>, sample: f64) -> f64 {
+    match current {
+        Some(current) => current * (1.0 - CLOCK_SMOOTHING) + sample * CLOCK_SMOOTHING,
+        None => sample,
+    }
+}
+
+/// Our clock in microseconds since the unix epoch, comparable with the server's timestamps.
+pub fn local_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64
+}
+
+/// When to call `sync_clock` next, and which call was answered last.
+#[derive(Resource)]
+pub struct ClockSyncTimer {
+    pub timer: Timer,
+    pub last_answered: Option<u64>,
+}
+
+impl Default for ClockSyncTimer {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(CLOCK_SYNC_SECS, TimerMode::Repeating),
+            last_answered: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_sample_is_taken_as_is() {
+        let mut clock = ServerClock::default();
+        clock.add_sample(1_000, 50_000, 1_200);
+
+        assert_eq!(clock.rtt(), Some(Duration::from_micros(200)));
+        assert_eq!(clock.offset_micros(), Some(48_900));
+        assert_eq!(clock.to_server(2_000), Some(50_900));
+        assert_eq!(clock.to_local(50_900), Some(2_000));
+    }
+
+    #[test]
+    fn later_samples_are_smoothed() {
+        let mut clock = ServerClock::default();
+        clock.add_sample(0, 10_000, 0);
+        clock.add_sample(0, 20_000, 0);
+
+        assert_eq!(clock.offset_micros(), Some(12_000));
+        assert_eq!(clock.samples, 2);
+    }
+
+    #[test]
+    fn samples_from_a_clock_going_backwards_are_ignored() {
+        let mut clock = ServerClock::default();
+        clock.add_sample(1_000, 50_000, 900);
+
+        assert!(!clock.is_synced());
+        assert_eq!(clock.server_now(), None);
+    }
+}
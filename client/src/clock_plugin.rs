@@ -0,0 +1,46 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::system::{Res, ResMut},
+    time::Time,
+};
+use spacetimedb_sdk::identity::identity;
+
+use crate::{
+    clock::{local_micros, ClockSyncTimer, ServerClock},
+    diagnostics::NetworkStats,
+    sync_clock, StdbClockSync,
+};
+
+/// Keeps `ServerClock` in sync by calling `sync_clock` every `CLOCK_SYNC_SECS` and reading
+/// the answer from our `StdbClockSync` row.
+pub struct ClockPlugin;
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ServerClock>()
+            .init_resource::<ClockSyncTimer>()
+            .add_systems(Update, (send_clock_sync, read_clock_sync));
+    }
+}
+
+fn send_clock_sync(time: Res<Time>, mut sync: ResMut<ClockSyncTimer>, stats: Res<NetworkStats>) {
+    // Without an identity we aren't connected, e.g. while watching a replay.
+    if identity().is_err() || !sync.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    stats.sent.count_reducer_call();
+    sync_clock(local_micros());
+}
+
+/// Adds a sample to `ServerClock` whenever our `StdbClockSync` row answers a new call.
+fn read_clock_sync(mut clock: ResMut<ServerClock>, mut sync: ResMut<ClockSyncTimer>) {
+    let Some(row) = identity().ok().and_then(StdbClockSync::filter_by_client_id) else {
+        return;
+    };
+    if sync.last_answered == Some(row.client_sent_micros) {
+        return;
+    }
+
+    sync.last_answered = Some(row.client_sent_micros);
+    clock.add_sample(row.client_sent_micros, row.server_micros, local_micros());
+}
@@ -0,0 +1,168 @@
+use spacetimedb_sdk::{identity::Identity, table::TableType};
+
+use crate::{
+    ban_player, configure_match, create_room, diagnostics::SentCounter, join_room, kick_player,
+    leave_room, mute_player, place_prop, remove_prop, send_team_message, set_day_length,
+    switch_team, teleport_player, StdbClient, StdbObject, StdbPlayer, StdbRoom, StdbTeam,
+    StdbVector2,
+};
+
+/// Kinds of objects `/prop` can place, they block paths on the server.
+const PROP_KINDS: [&str; 2] = ["rock", "tree"];
+/// How close the local player has to be to a prop to remove it with `/unprop`.
+const PROP_REACH: f32 = 80.0;
+
+/// Runs a chat message starting with `/` as a command, e.g. `/kick 1a2b3c bye`.
+/// Players are referred to by the leading hex of their identity shown in the chat log.
+/// Every command calls a single reducer, except the ones that only list things.
+pub fn run_command(command: &str, sent: &SentCounter) -> Result<(), String> {
+    let mut args = command.trim_start_matches('/').split_whitespace();
+
+    match args.next() {
+        Some("kick") => {
+            let target = find_client(args.next())?;
+            kick_player(target, rest(args));
+        }
+        Some("ban") => {
+            let target = find_client(args.next())?;
+            let minutes = parse_arg::<u64>(args.next(), "minutes")?;
+            ban_player(target, minutes_to_secs(minutes)?, rest(args));
+        }
+        Some("mute") => {
+            let target = find_client(args.next())?;
+            let minutes = parse_arg::<u64>(args.next(), "minutes")?;
+            mute_player(target, minutes_to_secs(minutes)?);
+        }
+        Some("tp") => {
+            let target = find_client(args.next())?;
+            let x = parse_arg::<f32>(args.next(), "x")?;
+            let y = parse_arg::<f32>(args.next(), "y")?;
+            teleport_player(target, StdbVector2 { x, y });
+        }
+        Some("prop") => {
+            let kind = args
+                .next()
+                .filter(|kind| PROP_KINDS.contains(kind))
+                .ok_or(format!("Usage: /prop <{}>", PROP_KINDS.join("|")))?;
+            place_prop(kind.to_string(), local_object()?.position);
+        }
+        Some("unprop") => remove_prop(nearest_prop()?),
+        Some("daylength") => {
+            let secs = parse_arg::<u64>(args.next(), "seconds")?;
+            set_day_length(secs);
+        }
+        Some("room") => match args.next() {
+            Some("create") => {
+                let capacity = parse_arg::<u32>(args.next(), "capacity")?;
+                create_room(rest(args), capacity);
+            }
+            Some("join") => {
+                let room_id = parse_arg::<u64>(args.next(), "room id")?;
+                join_room(room_id);
+            }
+            Some("leave") => leave_room(),
+            _ => {
+                return Err("Usage: /room create <capacity> <name> | join <id> | leave".to_string())
+            }
+        },
+        Some("match") => {
+            let duration_secs = parse_arg::<u32>(args.next(), "seconds")?;
+            let min_players = parse_arg::<u32>(args.next(), "min players")?;
+            configure_match(current_room_id()?, duration_secs, min_players);
+        }
+        Some("t") => send_team_message(rest(args)),
+        Some("team") => {
+            let team_id = parse_arg::<u64>(args.next(), "team id")?;
+            switch_team(team_id);
+        }
+        Some("teams") => {
+            let room_id = current_room_id()?;
+            for team in StdbTeam::iter() {
+                let players = StdbPlayer::iter()
+                    .filter(|player| player.room_id == room_id && player.team_id == team.team_id)
+                    .count();
+                println!("Team {}: {} ({} players)", team.team_id, team.name, players);
+            }
+            return Ok(());
+        }
+        Some("rooms") => {
+            for room in StdbRoom::iter() {
+                let players = StdbPlayer::iter()
+                    .filter(|player| player.room_id == room.room_id)
+                    .count();
+                println!(
+                    "Room {}: {} ({}/{})",
+                    room.room_id, room.name, players, room.capacity
+                );
+            }
+            return Ok(());
+        }
+        _ => return Err(format!("Unknown command: {}", command)),
+    }
+
+    sent.count_reducer_call();
+    Ok(())
+}
+
+/// Finds the only client whose identity starts with the hex `prefix`.
+fn find_client(prefix: Option<&str>) -> Result<Identity, String> {
+    let prefix = prefix.ok_or("Missing player id")?.to_lowercase();
+    let matches: Vec<Identity> = StdbClient::iter()
+        .map(|client| client.client_id)
+        .filter(|client_id| hex::encode(client_id.bytes()).starts_with(&prefix))
+        .collect();
+
+    match matches.as_slice() {
+        [client_id] => Ok(client_id.clone()),
+        [] => Err(format!("No player matches {}", prefix)),
+        _ => Err(format!("More than one player matches {}", prefix)),
+    }
+}
+
+/// Ban and mute lengths are typed in minutes, the server still checks they aren't too long.
+fn minutes_to_secs(minutes: u64) -> Result<u64, String> {
+    minutes
+        .checked_mul(60)
+        .ok_or(format!("{} minutes is too long", minutes))
+}
+
+/// The object the local player moves.
+fn local_object() -> Result<StdbObject, String> {
+    let identity = spacetimedb_sdk::identity::identity().map_err(|e| e.to_string())?;
+    StdbPlayer::filter_by_client_id(identity)
+        .and_then(|player| StdbObject::filter_by_object_id(player.object_id))
+        .ok_or("You haven't joined the game yet".to_string())
+}
+
+/// The `object_id` of the prop nearest the local player, if it's within `PROP_REACH`.
+fn nearest_prop() -> Result<u64, String> {
+    let player = local_object()?;
+    let distance = |object: &StdbObject| {
+        (object.position.x - player.position.x).hypot(object.position.y - player.position.y)
+    };
+    StdbObject::iter()
+        .filter(|object| {
+            object.room_id == player.room_id && PROP_KINDS.contains(&object.kind.as_str())
+        })
+        .filter(|object| distance(object) <= PROP_REACH)
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        .map(|object| object.object_id)
+        .ok_or("No prop close enough".to_string())
+}
+
+/// The room the local player is in.
+fn current_room_id() -> Result<u64, String> {
+    let identity = spacetimedb_sdk::identity::identity().map_err(|e| e.to_string())?;
+    StdbPlayer::filter_by_client_id(identity)
+        .map(|player| player.room_id)
+        .ok_or("You haven't joined the game yet".to_string())
+}
+
+fn parse_arg<T: std::str::FromStr>(arg: Option<&str>, name: &str) -> Result<T, String> {
+    arg.and_then(|arg| arg.parse().ok())
+        .ok_or(format!("Missing or invalid {}", name))
+}
+
+fn rest<'a>(args: impl Iterator<Item = &'a str>) -> String {
+    args.collect::<Vec<&str>>().join(" ")
+}
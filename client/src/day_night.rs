@@ -0,0 +1,88 @@
+use bevy::{ecs::component::Component, render::color::Color};
+
+use crate::StdbWorldTime;
+
+/// The scene's tint at points through the day, from midnight back round to midnight. Each
+/// is `(time of day, tint)`, the alpha is how strongly the tint covers the scene.
+pub const DAY_NIGHT_TINTS: [(f32, Color); 6] = [
+    (0.0, Color::rgba(0.02, 0.03, 0.15, 0.6)),
+    (0.22, Color::rgba(0.3, 0.2, 0.4, 0.35)),
+    (0.3, Color::rgba(1.0, 0.6, 0.3, 0.15)),
+    (0.5, Color::rgba(1.0, 1.0, 1.0, 0.0)),
+    (0.75, Color::rgba(0.9, 0.4, 0.3, 0.25)),
+    (1.0, Color::rgba(0.02, 0.03, 0.15, 0.6)),
+];
+
+/// Covers the scene with the tint for the time of day.
+#[derive(Component)]
+pub struct DayNightTint;
+
+/// How far through the day `server_micros` is, 0 at midnight and 0.5 at noon. This should
+/// match `StdbWorldTime::time_of_day` on the server.
+pub fn time_of_day(world_time: &StdbWorldTime, server_micros: u64) -> f32 {
+    if world_time.day_length_micros == 0 {
+        return 0.5;
+    }
+    let elapsed = server_micros.saturating_sub(world_time.epoch_micros);
+    (elapsed % world_time.day_length_micros) as f32 / world_time.day_length_micros as f32
+}
+
+/// The tint at `time_of_day`, blended between the two nearest `DAY_NIGHT_TINTS`.
+pub fn tint(time_of_day: f32) -> Color {
+    let t = time_of_day.clamp(0.0, 1.0);
+    for pair in DAY_NIGHT_TINTS.windows(2) {
+        let ((start, from), (end, to)) = (pair[0], pair[1]);
+        if t <= end {
+            let f = (t - start) / (end - start);
+            return Color::rgba(
+                from.r() + (to.r() - from.r()) * f,
+                from.g() + (to.g() - from.g()) * f,
+                from.b() + (to.b() - from.b()) * f,
+                from.a() + (to.a() - from.a()) * f,
+            );
+        }
+    }
+    DAY_NIGHT_TINTS[0].1
+}
+
+/// The background color at `time_of_day`, the ground darkened by the tint.
+pub fn clear_color(time_of_day: f32) -> Color {
+    let tint = tint(time_of_day);
+    let day = Color::rgb(0.4, 0.4, 0.4);
+    Color::rgb(
+        day.r() + (tint.r() * 0.4 - day.r()) * tint.a(),
+        day.g() + (tint.g() * 0.4 - day.g()) * tint.a(),
+        day.b() + (tint.b() * 0.4 - day.b()) * tint.a(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world_time() -> StdbWorldTime {
+        StdbWorldTime {
+            world_time_id: 0,
+            epoch_micros: 1_000_000,
+            day_length_micros: 100_000_000,
+            day: 0,
+        }
+    }
+
+    #[test]
+    fn the_day_wraps_around() {
+        let world_time = world_time();
+
+        assert_eq!(time_of_day(&world_time, 51_000_000), 0.5);
+        assert_eq!(time_of_day(&world_time, 126_000_000), 0.25);
+    }
+
+    #[test]
+    fn noon_is_clear_and_midnight_is_darkest() {
+        assert_eq!(tint(0.5).a(), 0.0);
+        assert_eq!(tint(0.0), tint(1.0));
+        assert!(DAY_NIGHT_TINTS
+            .iter()
+            .all(|(t, _)| tint(*t).a() <= tint(0.0).a()));
+    }
+}
@@ -0,0 +1,64 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    core_pipeline::clear_color::ClearColor,
+    ecs::{
+        query::With,
+        system::{Commands, Query, Res, ResMut},
+    },
+    render::color::Color,
+    ui::{node_bundles::NodeBundle, BackgroundColor, PositionType, Style, Val, ZIndex},
+};
+
+use crate::{
+    clock::{local_micros, ServerClock},
+    day_night::{clear_color, time_of_day, tint, DayNightTint},
+    world::WORLD_ID,
+    StdbWorldTime,
+};
+
+pub struct DayNightPlugin;
+impl Plugin for DayNightPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (init_day_night_tint,))
+            .add_systems(Update, (update_day_night,));
+    }
+}
+
+/// Spawns the tint over the whole scene, below the rest of the UI.
+fn init_day_night_tint(mut c: Commands) {
+    c.spawn((
+        DayNightTint,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..Default::default()
+            },
+            background_color: Color::NONE.into(),
+            z_index: ZIndex::Global(-1),
+            ..Default::default()
+        },
+    ));
+}
+
+/// Tints the scene and the background for the time of day published by the server. Our own
+/// clock stands in for the server's until it's synced.
+fn update_day_night(
+    clock: Option<Res<ServerClock>>,
+    mut clear: ResMut<ClearColor>,
+    mut q: Query<&mut BackgroundColor, With<DayNightTint>>,
+) {
+    let Some(world_time) = StdbWorldTime::filter_by_world_time_id(WORLD_ID) else {
+        return;
+    };
+
+    let now = clock
+        .and_then(|clock| clock.server_now())
+        .unwrap_or_else(local_micros);
+    let t = time_of_day(&world_time, now);
+    clear.0 = clear_color(t);
+    for mut background in &mut q {
+        background.0 = tint(t);
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    ecs::{component::Component, system::Resource},
+    input::keyboard::KeyCode,
+    time::{Timer, TimerMode},
+};
+
+use crate::StdbVector2;
+
+/// Shows and hides the network diagnostics overlay.
+pub const DIAGNOSTICS_KEY: KeyCode = KeyCode::F3;
+/// How much of each new round trip is mixed into `NetworkStats::rtt`.
+pub const RTT_SMOOTHING: f32 = 0.2;
+/// Moves waiting for their update before the oldest is forgotten, e.g. when the server
+/// clamped it and it never comes back as sent.
+const MAX_PENDING_MOVES: usize = 64;
+
+/// What the overlay shows, kept up to date whether or not it's open.
+#[derive(Resource, Default)]
+pub struct NetworkStats {
+    /// Smoothed time from sending a move to receiving the update of our object with it.
+    pub rtt: Option<Duration>,
+    /// Reducer calls sent over the last second.
+    pub reducer_calls_per_sec: u64,
+    /// Messages received over the last second, by `UncbMessage::name`.
+    pub messages_per_sec: BTreeMap<&'static str, u64>,
+    /// Every reducer call sent so far.
+    pub reducer_calls: u64,
+    /// Every message received so far, by `UncbMessage::name`.
+    pub messages: BTreeMap<&'static str, u64>,
+    /// Messages waiting in the channel at the start of the frame, see `UncbReceiver::backlog`.
+    pub backlog: usize,
+    pub players: usize,
+    pub objects: usize,
+    /// What's been sent since the stats were last updated.
+    pub sent: SentCounter,
+}
+
+/// Counts for the second in progress, moved into `NetworkStats` when `timer` finishes.
+#[derive(Resource)]
+pub struct NetworkStatsWindow {
+    pub timer: Timer,
+    pub reducer_calls: u64,
+    pub messages: BTreeMap<&'static str, u64>,
+}
+
+impl Default for NetworkStatsWindow {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            reducer_calls: 0,
+            messages: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct DiagnosticsPanel;
+
+#[derive(Component)]
+pub struct DiagnosticsText;
+
+#[derive(Default)]
+struct Sent {
+    reducer_calls: u64,
+    last_move: Option<StdbVector2>,
+    moves: VecDeque<(StdbVector2, Instant)>,
+}
+
+/// Reducers are called from the `SdkModule` as well as systems, so what's sent is counted
+/// here and taken by the `DiagnosticsPlugin` every frame. Clones count into the same totals.
+#[derive(Clone, Default)]
+pub struct SentCounter(Arc<Mutex<Sent>>);
+
+impl SentCounter {
+    /// Counts a reducer call towards `NetworkStats::reducer_calls`.
+    pub fn count_reducer_call(&self) {
+        self.0.lock().unwrap().reducer_calls += 1;
+    }
+
+    /// Counts an `update_player_pos` call, and remembers when `position` was sent so the
+    /// update it causes can be timed. Sending the same position again isn't timed, since the
+    /// server may not send an update for an object that didn't change.
+    pub fn track_move(&self, position: &StdbVector2) {
+        let mut sent = self.0.lock().unwrap();
+        sent.reducer_calls += 1;
+
+        let unchanged = sent
+            .last_move
+            .as_ref()
+            .is_some_and(|last| last.x == position.x && last.y == position.y);
+        if !unchanged {
+            sent.last_move = Some(position.clone());
+            if sent.moves.len() == MAX_PENDING_MOVES {
+                sent.moves.pop_front();
+            }
+            sent.moves.push_back((position.clone(), Instant::now()));
+        }
+    }
+
+    /// Takes the reducer calls counted since the last call.
+    pub fn take_reducer_calls(&self) -> u64 {
+        std::mem::take(&mut self.0.lock().unwrap().reducer_calls)
+    }
+
+    /// How long ago `position` was sent, if it was. Moves sent before it are dropped, their
+    /// updates were either skipped by the server or already timed.
+    pub fn take_move_rtt(&self, position: &StdbVector2) -> Option<Duration> {
+        let mut sent = self.0.lock().unwrap();
+        let index = sent
+            .moves
+            .iter()
+            .position(|(sent, _)| sent.x == position.x && sent.y == position.y)?;
+        let (_, sent_at) = sent.moves.drain(..=index).last()?;
+        Some(sent_at.elapsed())
+    }
+}
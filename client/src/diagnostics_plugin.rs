@@ -0,0 +1,268 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    ecs::{
+        event::EventReader,
+        query::With,
+        schedule::IntoSystemConfigs,
+        system::{Commands, Query, Res, ResMut},
+    },
+    hierarchy::BuildChildren,
+    input::{keyboard::KeyCode, Input},
+    render::{color::Color, view::Visibility},
+    text::{Text, TextStyle},
+    time::Time,
+    ui::{
+        node_bundles::{NodeBundle, TextBundle},
+        FlexDirection, PositionType, Style, UiRect, Val,
+    },
+};
+use leafwing_input_manager::action_state::ActionState;
+
+use crate::{
+    actions::GameActions,
+    clock::ServerClock,
+    diagnostics::{
+        DiagnosticsPanel, DiagnosticsText, NetworkStats, NetworkStatsWindow, DIAGNOSTICS_KEY,
+        RTT_SMOOTHING,
+    },
+    object::WorldObject,
+    player::Player,
+    uncb_receiver::{process_messages, UncbEvent, UncbMessage, UncbReceiver},
+};
+
+/// Keeps `NetworkStats` up to date and shows them on an overlay toggled with `DIAGNOSTICS_KEY`.
+pub struct DiagnosticsPlugin;
+impl Plugin for DiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetworkStats>()
+            .init_resource::<NetworkStatsWindow>()
+            .add_systems(Startup, (init_diagnostics_panel,))
+            .add_systems(
+                Update,
+                (
+                    toggle_diagnostics,
+                    count_messages,
+                    // Sampled before the channel is drained, afterwards it's nearly always empty.
+                    sample_backlog.before(process_messages),
+                    count_entities,
+                    roll_stats_window,
+                    refresh_diagnostics,
+                ),
+            );
+    }
+}
+
+/// Spawns the overlay hidden in the bottom right corner of the screen.
+fn init_diagnostics_panel(mut c: Commands) {
+    c.spawn((
+        DiagnosticsPanel,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                right: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.75).into(),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            DiagnosticsText,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 16.0,
+                    color: Color::WHITE,
+                    ..Default::default()
+                },
+            ),
+        ));
+    });
+}
+
+fn toggle_diagnostics(
+    keys: Res<Input<KeyCode>>,
+    mut q: Query<&mut Visibility, With<DiagnosticsPanel>>,
+) {
+    if !keys.just_pressed(DIAGNOSTICS_KEY) {
+        return;
+    }
+
+    for mut visibility in &mut q {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+/// Counts every `UncbEvent` by kind, and times the updates of our own object against the
+/// moves that caused them.
+fn count_messages(
+    mut stats: ResMut<NetworkStats>,
+    mut window: ResMut<NetworkStatsWindow>,
+    q_local: Query<&Player, With<ActionState<GameActions>>>,
+    mut er: EventReader<UncbEvent>,
+) {
+    let local_object_id = q_local.iter().next().map(|player| player.data.object_id);
+
+    for ev in er.read() {
+        let name = ev.message.name();
+        *stats.messages.entry(name).or_default() += 1;
+        *window.messages.entry(name).or_default() += 1;
+
+        match &ev.message {
+            UncbMessage::ObjectUpdated {
+                old: _,
+                new,
+                event: _,
+            } if Some(new.object_id) == local_object_id => {
+                if let Some(sample) = stats.sent.take_move_rtt(&new.position) {
+                    stats.rtt = Some(match stats.rtt {
+                        Some(rtt) => {
+                            rtt.mul_f32(1.0 - RTT_SMOOTHING) + sample.mul_f32(RTT_SMOOTHING)
+                        }
+                        None => sample,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let reducer_calls = stats.sent.take_reducer_calls();
+    stats.reducer_calls += reducer_calls;
+    window.reducer_calls += reducer_calls;
+}
+
+fn sample_backlog(receiver: Res<UncbReceiver>, mut stats: ResMut<NetworkStats>) {
+    stats.backlog = receiver.backlog();
+}
+
+fn count_entities(
+    mut stats: ResMut<NetworkStats>,
+    q_players: Query<(), With<Player>>,
+    q_objects: Query<(), With<WorldObject>>,
+) {
+    stats.players = q_players.iter().count();
+    stats.objects = q_objects.iter().count();
+}
+
+/// Moves the counts of the second that just ended into the per second rates.
+fn roll_stats_window(
+    time: Res<Time>,
+    mut stats: ResMut<NetworkStats>,
+    mut window: ResMut<NetworkStatsWindow>,
+) {
+    if !window.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    stats.reducer_calls_per_sec = std::mem::take(&mut window.reducer_calls);
+    stats.messages_per_sec = std::mem::take(&mut window.messages);
+}
+
+fn refresh_diagnostics(
+    stats: Res<NetworkStats>,
+    clock: Option<Res<ServerClock>>,
+    q_panel: Query<&Visibility, With<DiagnosticsPanel>>,
+    mut q_text: Query<&mut Text, With<DiagnosticsText>>,
+) {
+    if q_panel
+        .iter()
+        .all(|visibility| *visibility == Visibility::Hidden)
+    {
+        return;
+    }
+
+    let text = diagnostics_text(&stats, clock.as_deref());
+    for mut t in &mut q_text {
+        if t.sections[0].value != text {
+            t.sections[0].value = text.clone();
+        }
+    }
+}
+
+fn diagnostics_text(stats: &NetworkStats, clock: Option<&ServerClock>) -> String {
+    let rtt = match stats.rtt {
+        Some(rtt) => format!("{:.1}ms", rtt.as_secs_f64() * 1000.0),
+        None => "-".to_string(),
+    };
+    let server_clock = match clock.and_then(|clock| clock.offset_micros().zip(clock.rtt())) {
+        Some((offset, rtt)) => format!(
+            "{:+.1}ms (RTT {:.1}ms)",
+            offset as f64 / 1000.0,
+            rtt.as_secs_f64() * 1000.0
+        ),
+        None => "-".to_string(),
+    };
+    let mut text = format!(
+        "RTT: {}\nServer clock: {}\nReducer calls: {}/s\nBacklog: {}\nPlayers: {}  Objects: {}\nMessages:",
+        rtt, server_clock, stats.reducer_calls_per_sec, stats.backlog, stats.players, stats.objects
+    );
+
+    if stats.messages_per_sec.is_empty() {
+        text.push_str(" none");
+    }
+    for (name, count) in &stats.messages_per_sec {
+        text.push_str(&format!("\n  {}: {}/s", name, count));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{input::InputPlugin, MinimalPlugins};
+
+    use super::*;
+    use crate::uncb_receiver::{self, UncbSend};
+
+    fn test_app() -> (App, UncbSend) {
+        let (uncb_send, uncb_receiver) = uncb_receiver::channel();
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin, DiagnosticsPlugin))
+            .insert_resource(uncb_receiver)
+            .add_event::<UncbEvent>()
+            .add_systems(Update, process_messages);
+        (app, uncb_send)
+    }
+
+    #[test]
+    fn counts_messages_by_kind() {
+        let (mut app, send) = test_app();
+        send.unbounded_send(UncbMessage::SubscriptionApplied)
+            .unwrap();
+        send.unbounded_send(UncbMessage::Disconnected).unwrap();
+        send.unbounded_send(UncbMessage::SubscriptionApplied)
+            .unwrap();
+        assert_eq!(app.world.resource::<UncbReceiver>().backlog(), 3);
+        app.update();
+
+        assert_eq!(app.world.resource::<NetworkStats>().backlog, 3);
+        assert_eq!(app.world.resource::<UncbReceiver>().backlog(), 0);
+
+        app.update();
+        let stats = app.world.resource::<NetworkStats>();
+        assert_eq!(stats.messages.get("SubscriptionApplied"), Some(&2));
+        assert_eq!(stats.messages.get("Disconnected"), Some(&1));
+        assert_eq!(stats.backlog, 0);
+    }
+
+    #[test]
+    fn counts_reducer_calls() {
+        let (mut app, _send) = test_app();
+        app.update();
+        let sent = app.world.resource::<NetworkStats>().sent.clone();
+        sent.count_reducer_call();
+        sent.count_reducer_call();
+        app.update();
+
+        assert_eq!(app.world.resource::<NetworkStats>().reducer_calls, 2);
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Mutex,
+};
+
+use bevy::math::Rect;
+use spacetimedb_sdk::identity::Identity;
+
+use crate::{
+    module::ModuleApi,
+    uncb_receiver::{UncbMessage, UncbSend},
+    ReducerEvent, StdbObject, StdbPlayer, StdbVector2,
+};
+
+/// A reducer call made on the `FakeModule`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FakeCall {
+    CreatePlayer,
+    UpdatePlayerPos(StdbVector2),
+    MoveTo(StdbVector2),
+    SetAppearance(String, String),
+}
+
+/// An in-memory stand in for the module. Rows are changed the same way the SDK changes its
+/// client cache, and the matching `UncbMessage` is sent afterwards like the SDK callbacks do.
+pub struct FakeModule {
+    send: UncbSend,
+    state: Mutex<FakeState>,
+}
+
+#[derive(Default)]
+struct FakeState {
+    local_identity: Option<Identity>,
+    players: BTreeMap<u64, StdbPlayer>,
+    objects: BTreeMap<u64, StdbObject>,
+    paths: BTreeSet<u64>,
+    world_bounds: Option<Rect>,
+    calls: Vec<FakeCall>,
+}
+
+impl FakeModule {
+    pub fn new(send: UncbSend) -> Self {
+        Self {
+            send,
+            state: Mutex::new(FakeState::default()),
+        }
+    }
+
+    pub fn connect(&self, identity: Identity) {
+        self.state.lock().unwrap().local_identity = Some(identity);
+    }
+
+    pub fn set_world_bounds(&self, bounds: Rect) {
+        self.state.lock().unwrap().world_bounds = Some(bounds);
+    }
+
+    /// Every reducer called so far, oldest first.
+    pub fn calls(&self) -> Vec<FakeCall> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    /// Adds a player without a message, like the rows of a subscription.
+    pub fn load_player(&self, player: StdbPlayer) {
+        self.state
+            .lock()
+            .unwrap()
+            .players
+            .insert(player.object_id, player);
+    }
+
+    /// Adds an object without a message, like the rows of a subscription.
+    pub fn load_object(&self, object: StdbObject) {
+        self.state
+            .lock()
+            .unwrap()
+            .objects
+            .insert(object.object_id, object);
+    }
+
+    /// Marks the object as walking a path, or not, without a message.
+    pub fn load_path(&self, object_id: u64, walking: bool) {
+        let paths = &mut self.state.lock().unwrap().paths;
+        if walking {
+            paths.insert(object_id);
+        } else {
+            paths.remove(&object_id);
+        }
+    }
+
+    pub fn subscription_applied(&self) {
+        self.send(UncbMessage::SubscriptionApplied);
+    }
+
+    pub fn insert_player(&self, player: StdbPlayer, event: ReducerEvent) {
+        self.load_player(player.clone());
+        self.send(UncbMessage::PlayerInserted {
+            data: player,
+            event,
+        });
+    }
+
+    pub fn update_player(&self, new: StdbPlayer, event: ReducerEvent) {
+        let old = self
+            .state
+            .lock()
+            .unwrap()
+            .players
+            .insert(new.object_id, new.clone())
+            .expect("Updated a player that doesn't exist");
+        self.send(UncbMessage::PlayerUpdated { old, new, event });
+    }
+
+    pub fn delete_player(&self, object_id: u64, event: Option<ReducerEvent>) {
+        let data = self
+            .state
+            .lock()
+            .unwrap()
+            .players
+            .remove(&object_id)
+            .expect("Deleted a player that doesn't exist");
+        self.send(UncbMessage::PlayerRemoved { data, event });
+    }
+
+    fn send(&self, message: UncbMessage) {
+        self.send.unbounded_send(message).unwrap();
+    }
+
+    fn call(&self, call: FakeCall) {
+        self.state.lock().unwrap().calls.push(call);
+    }
+}
+
+impl ModuleApi for FakeModule {
+    fn local_identity(&self) -> Option<Identity> {
+        self.state.lock().unwrap().local_identity.clone()
+    }
+
+    fn players(&self) -> Vec<StdbPlayer> {
+        self.state
+            .lock()
+            .unwrap()
+            .players
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    fn player(&self, object_id: u64) -> Option<StdbPlayer> {
+        self.state.lock().unwrap().players.get(&object_id).cloned()
+    }
+
+    fn object(&self, object_id: u64) -> Option<StdbObject> {
+        self.state.lock().unwrap().objects.get(&object_id).cloned()
+    }
+
+    fn world_bounds(&self) -> Option<Rect> {
+        self.state.lock().unwrap().world_bounds
+    }
+
+    fn has_path(&self, object_id: u64) -> bool {
+        self.state.lock().unwrap().paths.contains(&object_id)
+    }
+
+    fn create_player(&self) {
+        self.call(FakeCall::CreatePlayer);
+    }
+
+    fn update_player_pos(&self, position: StdbVector2) {
+        self.call(FakeCall::UpdatePlayerPos(position));
+    }
+
+    fn move_to(&self, destination: StdbVector2) {
+        self.call(FakeCall::MoveTo(destination));
+    }
+
+    fn set_appearance(&self, color: String, skin: String) {
+        self.call(FakeCall::SetAppearance(color, skin));
+    }
+}
@@ -0,0 +1,11 @@
+use bevy::ecs::component::Component;
+
+/// The furthest the local player can be from an item drop and still pick it up,
+/// this should match `PICKUP_RANGE` on the server.
+pub const PICKUP_RANGE: f32 = 75.0;
+
+#[derive(Component)]
+pub struct InventoryPanel;
+
+#[derive(Component)]
+pub struct InventoryText;
@@ -0,0 +1,160 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    ecs::{
+        event::EventReader,
+        query::With,
+        system::{Commands, Query, Res},
+    },
+    hierarchy::BuildChildren,
+    render::{color::Color, view::Visibility},
+    text::{Text, TextStyle},
+    transform::components::Transform,
+    ui::{
+        node_bundles::{NodeBundle, TextBundle},
+        FlexDirection, PositionType, Style, UiRect, Val,
+    },
+};
+use leafwing_input_manager::action_state::ActionState;
+use spacetimedb_sdk::table::TableType;
+
+use crate::{
+    actions::GameActions,
+    diagnostics::NetworkStats,
+    inventory::{InventoryPanel, InventoryText, PICKUP_RANGE},
+    pickup_item, stdb_to_vec2,
+    uncb_receiver::{UncbEvent, UncbMessage},
+    StdbInventoryItem, StdbItem, StdbItemDrop, StdbObject,
+};
+
+pub struct InventoryPlugin;
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (init_inventory_panel,))
+            .add_systems(Update, (toggle_inventory, refresh_inventory, pickup_items));
+    }
+}
+
+/// Spawns the inventory panel hidden in the top right corner of the screen.
+fn init_inventory_panel(mut c: Commands) {
+    c.spawn((
+        InventoryPanel,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.75).into(),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            InventoryText,
+            TextBundle::from_section(
+                inventory_text(),
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                    ..Default::default()
+                },
+            ),
+        ));
+    });
+}
+
+/// Shows or hides the inventory panel when the local player presses `GameActions::Inventory`.
+/// The text is rebuilt on open since stacks from the initial subscription never send a message.
+fn toggle_inventory(
+    q_actions: Query<&ActionState<GameActions>>,
+    mut q_panel: Query<&mut Visibility, With<InventoryPanel>>,
+    mut q_text: Query<&mut Text, With<InventoryText>>,
+) {
+    for action_state in &q_actions {
+        if !action_state.just_pressed(GameActions::Inventory) {
+            continue;
+        }
+
+        for mut visibility in &mut q_panel {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Visible,
+                _ => Visibility::Hidden,
+            };
+        }
+        for mut text in &mut q_text {
+            text.sections[0].value = inventory_text();
+        }
+    }
+}
+
+/// Listens for the `UncbMessage::InventoryItem*` messages and rebuilds the inventory text.
+fn refresh_inventory(mut q: Query<&mut Text, With<InventoryText>>, mut er: EventReader<UncbEvent>) {
+    let mut changed = false;
+
+    for ev in er.read() {
+        match &ev.message {
+            UncbMessage::InventoryItemInserted { .. }
+            | UncbMessage::InventoryItemUpdated { .. }
+            | UncbMessage::InventoryItemRemoved { .. } => {
+                changed = true;
+            }
+            _ => {}
+        }
+    }
+
+    if changed {
+        for mut text in &mut q {
+            text.sections[0].value = inventory_text();
+        }
+    }
+}
+
+/// When the local player presses `GameActions::Pickup` we ask the server to pick up
+/// the nearest item drop within `PICKUP_RANGE`.
+fn pickup_items(q: Query<(&ActionState<GameActions>, &Transform)>, stats: Res<NetworkStats>) {
+    for (action_state, transform) in &q {
+        if !action_state.just_pressed(GameActions::Pickup) {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        let nearest = StdbItemDrop::iter()
+            .filter_map(|drop| StdbObject::filter_by_object_id(drop.object_id))
+            .map(|object| {
+                let distance = position.distance(stdb_to_vec2(&object.position));
+                (object.object_id, distance)
+            })
+            .filter(|(_, distance)| *distance <= PICKUP_RANGE)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((object_id, _)) = nearest {
+            stats.sent.count_reducer_call();
+            pickup_item(object_id);
+        }
+    }
+}
+
+/// Lists every stack the local client owns, one per line.
+fn inventory_text() -> String {
+    let mut text = "Inventory".to_string();
+
+    if let Ok(identity) = spacetimedb_sdk::identity::identity() {
+        for entry in StdbInventoryItem::iter() {
+            if entry.client_id != identity {
+                continue;
+            }
+
+            let name = match StdbItem::filter_by_item_id(entry.item_id) {
+                Some(item) => item.name,
+                None => "Unknown".to_string(),
+            };
+            text.push_str(&format!("\n{} x{}", name, entry.quantity));
+        }
+    }
+
+    text
+}
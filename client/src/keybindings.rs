@@ -0,0 +1,374 @@
+use std::{collections::HashMap, fs, io, path::PathBuf};
+
+use bevy::{
+    ecs::{component::Component, system::Resource},
+    input::keyboard::KeyCode,
+    log::warn,
+};
+
+use crate::{actions::GameActions, CREDS_DIR};
+
+/// The file the keybindings are saved to, next to the saved credentials in the home directory.
+pub const KEYBINDINGS_FILE: &str = "keybindings.txt";
+/// Opens and closes the keybindings screen, it can't be rebound so it's never lost.
+pub const KEYBINDINGS_KEY: KeyCode = KeyCode::F1;
+
+/// Keys that can be bound. `Return` opens the chat and `Escape` cancels a rebinding, so
+/// neither is here, and neither is `KEYBINDINGS_KEY`.
+pub const REBINDABLE_KEYS: [KeyCode; 56] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Space,
+    KeyCode::Tab,
+    KeyCode::Back,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Apostrophe,
+    KeyCode::BracketLeft,
+    KeyCode::BracketRight,
+    KeyCode::Minus,
+    KeyCode::Equals,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::AltLeft,
+];
+
+/// Everything on the keyboard that can be rebound. `GameActions::Move` takes four keys, one
+/// per direction, every other action takes one.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Binding {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Pickup,
+    Inventory,
+    CycleColor,
+    CycleSkin,
+    Leaderboard,
+}
+
+/// Every `Binding`, in the order the keybindings screen lists them.
+pub const ALL_BINDINGS: [Binding; 9] = [
+    Binding::MoveUp,
+    Binding::MoveDown,
+    Binding::MoveLeft,
+    Binding::MoveRight,
+    Binding::Pickup,
+    Binding::Inventory,
+    Binding::CycleColor,
+    Binding::CycleSkin,
+    Binding::Leaderboard,
+];
+
+impl Binding {
+    /// How the binding is shown on the keybindings screen.
+    pub fn label(self) -> &'static str {
+        match self {
+            Binding::MoveUp => "Move up",
+            Binding::MoveDown => "Move down",
+            Binding::MoveLeft => "Move left",
+            Binding::MoveRight => "Move right",
+            Binding::Pickup => "Pick up",
+            Binding::Inventory => "Inventory",
+            Binding::CycleColor => "Cycle color",
+            Binding::CycleSkin => "Cycle skin",
+            Binding::Leaderboard => "Leaderboard",
+        }
+    }
+
+    /// How the binding is named in `KEYBINDINGS_FILE`.
+    pub fn id(self) -> &'static str {
+        match self {
+            Binding::MoveUp => "move_up",
+            Binding::MoveDown => "move_down",
+            Binding::MoveLeft => "move_left",
+            Binding::MoveRight => "move_right",
+            Binding::Pickup => "pickup",
+            Binding::Inventory => "inventory",
+            Binding::CycleColor => "cycle_color",
+            Binding::CycleSkin => "cycle_skin",
+            Binding::Leaderboard => "leaderboard",
+        }
+    }
+
+    pub fn default_key(self) -> KeyCode {
+        match self {
+            Binding::MoveUp => KeyCode::W,
+            Binding::MoveDown => KeyCode::S,
+            Binding::MoveLeft => KeyCode::A,
+            Binding::MoveRight => KeyCode::D,
+            Binding::Pickup => KeyCode::E,
+            Binding::Inventory => KeyCode::I,
+            Binding::CycleColor => KeyCode::C,
+            Binding::CycleSkin => KeyCode::V,
+            Binding::Leaderboard => KeyCode::L,
+        }
+    }
+
+    /// The action the key presses, `None` for the `GameActions::Move` directions.
+    pub fn action(self) -> Option<GameActions> {
+        match self {
+            Binding::MoveUp | Binding::MoveDown | Binding::MoveLeft | Binding::MoveRight => None,
+            Binding::Pickup => Some(GameActions::Pickup),
+            Binding::Inventory => Some(GameActions::Inventory),
+            Binding::CycleColor => Some(GameActions::CycleColor),
+            Binding::CycleSkin => Some(GameActions::CycleSkin),
+            Binding::Leaderboard => Some(GameActions::Leaderboard),
+        }
+    }
+}
+
+/// The key of every `Binding`, loaded from and saved to `KEYBINDINGS_FILE`. The local
+/// player's `InputMap` is built from these, see `actions::input_map`.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct Keybindings {
+    keys: HashMap<Binding, KeyCode>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            keys: ALL_BINDINGS
+                .iter()
+                .map(|binding| (*binding, binding.default_key()))
+                .collect(),
+        }
+    }
+}
+
+impl Keybindings {
+    pub fn key(&self, binding: Binding) -> KeyCode {
+        self.keys
+            .get(&binding)
+            .copied()
+            .unwrap_or(binding.default_key())
+    }
+
+    /// Binds `key` to `binding`, unless it can't be bound or another binding already uses it.
+    pub fn rebind(&mut self, binding: Binding, key: KeyCode) -> Result<(), String> {
+        if !REBINDABLE_KEYS.contains(&key) {
+            return Err(format!("{:?} can't be bound", key));
+        }
+        if let Some(other) = self.binding_of(key).filter(|other| *other != binding) {
+            return Err(format!("{:?} is already bound to {}", key, other.label()));
+        }
+
+        self.keys.insert(binding, key);
+        Ok(())
+    }
+
+    /// The binding `key` is bound to, if any.
+    pub fn binding_of(&self, key: KeyCode) -> Option<Binding> {
+        ALL_BINDINGS
+            .iter()
+            .copied()
+            .find(|binding| self.key(*binding) == key)
+    }
+
+    /// Reads `KEYBINDINGS_FILE`, falling back to the defaults if there is none or it's broken.
+    pub fn load() -> Self {
+        let Some(path) = keybindings_path() else {
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).unwrap_or_else(|e| {
+                warn!("Ignoring {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                warn!("Failed to read {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = keybindings_path() else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "No home directory to save keybindings in",
+            ));
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_text())
+    }
+
+    /// Parses one `id = Key` line per binding. Bindings that aren't listed keep their default.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut keybindings = Self::default();
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let (id, key) = line
+                .split_once('=')
+                .ok_or(format!("Expected `binding = key`, got {}", line))?;
+            let binding = ALL_BINDINGS
+                .iter()
+                .copied()
+                .find(|binding| binding.id() == id.trim())
+                .ok_or(format!("Unknown binding {}", id.trim()))?;
+            let key = REBINDABLE_KEYS
+                .iter()
+                .copied()
+                .find(|candidate| format!("{:?}", candidate) == key.trim())
+                .ok_or(format!("Unknown key {}", key.trim()))?;
+            keybindings.keys.insert(binding, key);
+        }
+
+        // Every key has to be unique, or one action would shadow another.
+        for binding in ALL_BINDINGS {
+            if keybindings.binding_of(keybindings.key(binding)) != Some(binding) {
+                return Err(format!(
+                    "{:?} is bound more than once",
+                    keybindings.key(binding)
+                ));
+            }
+        }
+
+        Ok(keybindings)
+    }
+
+    pub fn to_text(&self) -> String {
+        ALL_BINDINGS
+            .iter()
+            .map(|binding| format!("{} = {:?}\n", binding.id(), self.key(*binding)))
+            .collect()
+    }
+}
+
+fn keybindings_path() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(CREDS_DIR).join(KEYBINDINGS_FILE))
+}
+
+/// Which `Binding` is waiting for a key on the keybindings screen, and the result of the
+/// last rebinding.
+#[derive(Resource, Default)]
+pub struct Rebinding {
+    pub waiting: Option<Binding>,
+    pub status: String,
+}
+
+#[derive(Component)]
+pub struct KeybindingsPanel;
+
+/// A row on the keybindings screen, clicking it waits for a new key for the binding.
+#[derive(Component)]
+pub struct KeybindingButton(pub Binding);
+
+/// The text of a `KeybindingButton`, showing the binding's key.
+#[derive(Component)]
+pub struct KeybindingLabel(pub Binding);
+
+#[derive(Component)]
+pub struct KeybindingsResetButton;
+
+#[derive(Component)]
+pub struct KeybindingsStatus;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_keybindings_load_the_same() {
+        let mut keybindings = Keybindings::default();
+        keybindings.rebind(Binding::MoveUp, KeyCode::Up).unwrap();
+        keybindings.rebind(Binding::Pickup, KeyCode::Space).unwrap();
+
+        assert_eq!(Keybindings::parse(&keybindings.to_text()), Ok(keybindings));
+    }
+
+    #[test]
+    fn missing_bindings_keep_their_defaults() {
+        let keybindings = Keybindings::parse("pickup = F\n").unwrap();
+
+        assert_eq!(keybindings.key(Binding::Pickup), KeyCode::F);
+        assert_eq!(keybindings.key(Binding::MoveUp), KeyCode::W);
+    }
+
+    #[test]
+    fn rebinding_to_a_used_key_is_refused() {
+        let mut keybindings = Keybindings::default();
+
+        assert!(keybindings.rebind(Binding::Pickup, KeyCode::W).is_err());
+        assert_eq!(keybindings.key(Binding::Pickup), KeyCode::E);
+    }
+
+    #[test]
+    fn rebinding_to_the_same_key_is_fine() {
+        let mut keybindings = Keybindings::default();
+
+        assert!(keybindings.rebind(Binding::Pickup, KeyCode::E).is_ok());
+    }
+
+    #[test]
+    fn reserved_keys_are_refused() {
+        let mut keybindings = Keybindings::default();
+
+        assert!(keybindings
+            .rebind(Binding::Pickup, KeyCode::Return)
+            .is_err());
+        assert!(keybindings
+            .rebind(Binding::Pickup, KEYBINDINGS_KEY)
+            .is_err());
+    }
+
+    #[test]
+    fn duplicate_keys_in_the_file_are_rejected() {
+        assert!(Keybindings::parse("pickup = W\n").is_err());
+    }
+
+    #[test]
+    fn unknown_lines_are_rejected() {
+        assert!(Keybindings::parse("jump = Space\n").is_err());
+        assert!(Keybindings::parse("pickup = Nope\n").is_err());
+        assert!(Keybindings::parse("pickup\n").is_err());
+    }
+}
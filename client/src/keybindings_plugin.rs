@@ -0,0 +1,265 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    ecs::{
+        query::{Changed, With, Without},
+        system::{Commands, Query, Res, ResMut},
+    },
+    hierarchy::BuildChildren,
+    input::{keyboard::KeyCode, Input},
+    log::{info, warn},
+    render::{color::Color, view::Visibility},
+    text::{Text, TextStyle},
+    ui::{
+        node_bundles::{ButtonBundle, NodeBundle, TextBundle},
+        AlignSelf, BackgroundColor, FlexDirection, Interaction, PositionType, Style, UiRect, Val,
+    },
+};
+use leafwing_input_manager::{input_map::InputMap, plugin::ToggleActions};
+
+use crate::{
+    actions::{input_map, GameActions},
+    keybindings::{
+        Binding, KeybindingButton, KeybindingLabel, Keybindings, KeybindingsPanel,
+        KeybindingsResetButton, KeybindingsStatus, Rebinding, ALL_BINDINGS, KEYBINDINGS_KEY,
+    },
+};
+
+/// The keybindings screen, opened with `KEYBINDINGS_KEY`. Clicking a binding waits for the
+/// next key press, and every change is saved and applied to the local player right away.
+pub struct KeybindingsPlugin;
+impl Plugin for KeybindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Keybindings::load())
+            .init_resource::<Rebinding>()
+            .add_systems(Startup, (init_keybindings_panel,))
+            .add_systems(
+                Update,
+                (
+                    toggle_keybindings,
+                    click_keybindings,
+                    capture_key,
+                    apply_keybindings,
+                    refresh_keybindings_panel,
+                ),
+            );
+    }
+}
+
+const BUTTON_COLOR: Color = Color::rgb(0.2, 0.2, 0.2);
+const WAITING_COLOR: Color = Color::rgb(0.4, 0.3, 0.1);
+
+/// Spawns the keybindings screen hidden in the center of the screen, with a button per
+/// binding and one to reset them all.
+fn init_keybindings_panel(mut c: Commands, keybindings: Res<Keybindings>) {
+    let text_style = TextStyle {
+        font_size: 20.0,
+        color: Color::WHITE,
+        ..Default::default()
+    };
+    let button_style = Style {
+        padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+        margin: UiRect::top(Val::Px(4.0)),
+        ..Default::default()
+    };
+
+    c.spawn((
+        KeybindingsPanel,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                align_self: AlignSelf::Center,
+                left: Val::Percent(35.0),
+                width: Val::Percent(30.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            format!("Keybindings ({:?} to close)", KEYBINDINGS_KEY),
+            text_style.clone(),
+        ));
+
+        for binding in ALL_BINDINGS {
+            parent
+                .spawn((
+                    KeybindingButton(binding),
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: BUTTON_COLOR.into(),
+                        ..Default::default()
+                    },
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        KeybindingLabel(binding),
+                        TextBundle::from_section(
+                            binding_text(&keybindings, binding),
+                            text_style.clone(),
+                        ),
+                    ));
+                });
+        }
+
+        parent
+            .spawn((
+                KeybindingsResetButton,
+                ButtonBundle {
+                    style: button_style.clone(),
+                    background_color: BUTTON_COLOR.into(),
+                    ..Default::default()
+                },
+            ))
+            .with_children(|button| {
+                button.spawn(TextBundle::from_section(
+                    "Reset to defaults",
+                    text_style.clone(),
+                ));
+            });
+
+        parent.spawn((
+            KeybindingsStatus,
+            TextBundle::from_section("", text_style.clone()),
+        ));
+    });
+}
+
+/// Shows or hides the keybindings screen. Actions are turned off while it's open, so keys
+/// pressed to rebind something don't also move or pick up.
+fn toggle_keybindings(
+    keys: Res<Input<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut toggle_actions: ResMut<ToggleActions<GameActions>>,
+    mut q: Query<&mut Visibility, With<KeybindingsPanel>>,
+) {
+    for mut visibility in &mut q {
+        if keys.just_pressed(KEYBINDINGS_KEY) {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Visible,
+                _ => Visibility::Hidden,
+            };
+            if *visibility == Visibility::Hidden {
+                rebinding.waiting = None;
+                rebinding.status.clear();
+                toggle_actions.enabled = true;
+            }
+        }
+
+        // The chat turns actions back on when it closes, even if we're still open.
+        if *visibility == Visibility::Visible && toggle_actions.enabled {
+            toggle_actions.enabled = false;
+        }
+    }
+}
+
+/// Clicking a binding waits for its new key, clicking reset goes back to the defaults.
+fn click_keybindings(
+    mut keybindings: ResMut<Keybindings>,
+    mut rebinding: ResMut<Rebinding>,
+    q_bindings: Query<(&Interaction, &KeybindingButton), Changed<Interaction>>,
+    q_reset: Query<&Interaction, (Changed<Interaction>, With<KeybindingsResetButton>)>,
+) {
+    for (interaction, button) in &q_bindings {
+        if *interaction == Interaction::Pressed {
+            rebinding.waiting = Some(button.0);
+            rebinding.status = format!("Press a key for {}, Escape to cancel", button.0.label());
+        }
+    }
+
+    for interaction in &q_reset {
+        if *interaction == Interaction::Pressed {
+            *keybindings = Keybindings::default();
+            rebinding.waiting = None;
+            rebinding.status = "Reset to defaults".to_string();
+        }
+    }
+}
+
+/// Binds the next key pressed to the binding waiting for one, unless another binding
+/// already uses it.
+fn capture_key(
+    keys: Res<Input<KeyCode>>,
+    mut keybindings: ResMut<Keybindings>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    let Some(binding) = rebinding.waiting else {
+        return;
+    };
+    let Some(key) = keys.get_just_pressed().next().copied() else {
+        return;
+    };
+
+    if key == KeyCode::Escape {
+        rebinding.waiting = None;
+        rebinding.status.clear();
+        return;
+    }
+
+    // Rebinds a copy so a refused key doesn't count as a change and get saved.
+    let mut rebound = keybindings.clone();
+    rebinding.status = match rebound.rebind(binding, key) {
+        Ok(()) => {
+            *keybindings = rebound;
+            rebinding.waiting = None;
+            format!("{} is now {:?}", binding.label(), key)
+        }
+        Err(e) => e,
+    };
+}
+
+/// Saves the keybindings whenever they change and rebuilds the local player's `InputMap`
+/// from them, keeping the gamepad it was given.
+fn apply_keybindings(keybindings: Res<Keybindings>, mut q: Query<&mut InputMap<GameActions>>) {
+    if !keybindings.is_changed() || keybindings.is_added() {
+        return;
+    }
+
+    match keybindings.save() {
+        Ok(()) => info!("Saved keybindings"),
+        Err(e) => warn!("Failed to save keybindings: {}", e),
+    }
+
+    for mut map in &mut q {
+        let gamepad = map.gamepad();
+        *map = input_map(&keybindings);
+        if let Some(gamepad) = gamepad {
+            map.set_gamepad(gamepad);
+        }
+    }
+}
+
+fn refresh_keybindings_panel(
+    keybindings: Res<Keybindings>,
+    rebinding: Res<Rebinding>,
+    mut q_labels: Query<(&KeybindingLabel, &mut Text), Without<KeybindingsStatus>>,
+    mut q_buttons: Query<(&KeybindingButton, &mut BackgroundColor)>,
+    mut q_status: Query<&mut Text, With<KeybindingsStatus>>,
+) {
+    if !keybindings.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+
+    for (label, mut text) in &mut q_labels {
+        text.sections[0].value = binding_text(&keybindings, label.0);
+    }
+    for (button, mut color) in &mut q_buttons {
+        *color = if rebinding.waiting == Some(button.0) {
+            WAITING_COLOR
+        } else {
+            BUTTON_COLOR
+        }
+        .into();
+    }
+    for mut text in &mut q_status {
+        text.sections[0].value = rebinding.status.clone();
+    }
+}
+
+fn binding_text(keybindings: &Keybindings, binding: Binding) -> String {
+    format!("{}: {:?}", binding.label(), keybindings.key(binding))
+}
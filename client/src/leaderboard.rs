@@ -0,0 +1,20 @@
+use bevy::{
+    ecs::{component::Component, system::Resource},
+    time::{Timer, TimerMode},
+};
+
+#[derive(Component)]
+pub struct LeaderboardPanel;
+
+#[derive(Component)]
+pub struct LeaderboardText;
+
+/// Limits how often the leaderboard text is rebuilt while it's open.
+#[derive(Resource)]
+pub struct LeaderboardTimer(pub Timer);
+
+impl Default for LeaderboardTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(1.0, TimerMode::Repeating))
+    }
+}
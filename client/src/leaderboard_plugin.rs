@@ -0,0 +1,146 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    ecs::{
+        query::With,
+        system::{Commands, Query, Res, ResMut},
+    },
+    hierarchy::BuildChildren,
+    render::{color::Color, view::Visibility},
+    text::{Text, TextStyle},
+    time::Time,
+    ui::{
+        node_bundles::{NodeBundle, TextBundle},
+        FlexDirection, PositionType, Style, UiRect, Val,
+    },
+};
+use leafwing_input_manager::action_state::ActionState;
+use spacetimedb_sdk::table::TableType;
+
+use crate::{
+    actions::GameActions,
+    identity_leading_hex,
+    leaderboard::{LeaderboardPanel, LeaderboardText, LeaderboardTimer},
+    StdbLeaderboardEntry, StdbPlayerStats,
+};
+
+pub struct LeaderboardPlugin;
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LeaderboardTimer>()
+            .add_systems(Startup, (init_leaderboard_panel,))
+            .add_systems(Update, (toggle_leaderboard, refresh_leaderboard));
+    }
+}
+
+/// Spawns the leaderboard panel hidden below the room label.
+fn init_leaderboard_panel(mut c: Commands) {
+    c.spawn((
+        LeaderboardPanel,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(40.0),
+                left: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.75).into(),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            LeaderboardText,
+            TextBundle::from_section(
+                leaderboard_text(),
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                    ..Default::default()
+                },
+            ),
+        ));
+    });
+}
+
+/// Shows or hides the leaderboard panel when the local player presses `GameActions::Leaderboard`.
+fn toggle_leaderboard(
+    q_actions: Query<&ActionState<GameActions>>,
+    mut q_panel: Query<&mut Visibility, With<LeaderboardPanel>>,
+    mut q_text: Query<&mut Text, With<LeaderboardText>>,
+) {
+    for action_state in &q_actions {
+        if !action_state.just_pressed(GameActions::Leaderboard) {
+            continue;
+        }
+
+        for mut visibility in &mut q_panel {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Visible,
+                _ => Visibility::Hidden,
+            };
+        }
+        for mut text in &mut q_text {
+            text.sections[0].value = leaderboard_text();
+        }
+    }
+}
+
+/// Rebuilds the leaderboard text every `LeaderboardTimer` while the panel is open, play
+/// time keeps counting up for everyone who's online.
+fn refresh_leaderboard(
+    time: Res<Time>,
+    mut timer: ResMut<LeaderboardTimer>,
+    q_panel: Query<&Visibility, With<LeaderboardPanel>>,
+    mut q_text: Query<&mut Text, With<LeaderboardText>>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    if q_panel
+        .iter()
+        .all(|visibility| *visibility == Visibility::Hidden)
+    {
+        return;
+    }
+
+    for mut text in &mut q_text {
+        text.sections[0].value = leaderboard_text();
+    }
+}
+
+/// Lists the top players by play time, then the local player's own stats.
+fn leaderboard_text() -> String {
+    let mut entries: Vec<StdbLeaderboardEntry> = StdbLeaderboardEntry::iter().collect();
+    entries.sort_by_key(|entry| entry.rank);
+
+    let mut text = "Leaderboard".to_string();
+    for entry in entries {
+        text.push_str(&format!(
+            "\n{}. {}  {}  {:.0} traveled  {} sessions",
+            entry.rank,
+            identity_leading_hex(&entry.client_id),
+            format_play_time(entry.play_time_secs),
+            entry.distance_traveled,
+            entry.sessions
+        ));
+    }
+
+    let own_stats = spacetimedb_sdk::identity::identity()
+        .ok()
+        .and_then(StdbPlayerStats::filter_by_client_id);
+    if let Some(stats) = own_stats {
+        text.push_str(&format!(
+            "\n\nYou: {:.0} traveled  {} sessions",
+            stats.distance_traveled, stats.sessions
+        ));
+    }
+
+    text
+}
+
+fn format_play_time(secs: u64) -> String {
+    format!("{}h {:02}m", secs / 3600, secs / 60 % 60)
+}
@@ -0,0 +1,464 @@
+use bevy::{log::info, math::Vec2};
+use spacetimedb_sdk::{
+    identity::{load_credentials, once_on_connect, save_credentials, Credentials, Identity},
+    on_disconnect, subscribe,
+    table::{TableType, TableWithPrimaryKey},
+    Address,
+};
+
+pub mod actions;
+pub mod appearance;
+pub mod camera;
+pub mod camera_plugin;
+pub mod chat;
+pub mod chat_plugin;
+pub mod clock;
+pub mod clock_plugin;
+pub mod commands;
+pub mod day_night;
+pub mod day_night_plugin;
+pub mod diagnostics;
+pub mod diagnostics_plugin;
+#[cfg(test)]
+pub mod fake_module;
+pub mod inventory;
+pub mod inventory_plugin;
+pub mod keybindings;
+pub mod keybindings_plugin;
+pub mod leaderboard;
+pub mod leaderboard_plugin;
+pub mod match_plugin;
+pub mod matches;
+pub mod minimap;
+pub mod minimap_plugin;
+pub mod module;
+pub mod module_bindings;
+pub mod object;
+pub mod object_plugin;
+pub mod player;
+pub mod player_plugin;
+pub mod portal;
+pub mod portal_plugin;
+pub mod recording;
+pub mod recording_plugin;
+pub mod replay_plugin;
+pub mod room;
+pub mod room_plugin;
+pub mod team;
+pub mod uncb_receiver;
+pub mod world;
+pub mod zone;
+pub mod zone_plugin;
+
+use module_bindings::*;
+use uncb_receiver::{UncbMessage, UncbSend};
+
+const SPACETIMEDB_URI: &str = "http://localhost:3000";
+const DB_NAME: &str = "spacetime-bevy-game";
+const CREDS_DIR: &str = ".spacetime-bevy-game";
+const DEBUG_MODE: bool = true;
+
+/// Connects to the module, without `credentials` the server gives us a new identity.
+pub fn connect_to_db(credentials: Option<Credentials>) {
+    connect(SPACETIMEDB_URI, DB_NAME, credentials).expect("Failed to connect");
+}
+
+/// The credentials saved by `save_credentials_on_connect`, never any in `DEBUG_MODE`.
+pub fn saved_credentials() -> Option<Credentials> {
+    if DEBUG_MODE {
+        None
+    } else {
+        load_credentials(CREDS_DIR).expect("Error reading stored credentials")
+    }
+}
+
+/// Every table that isn't scoped to a room or team, all of their rows are subscribed to.
+const GLOBAL_TABLES: &[&str] = &[
+    "StdbClient",
+    "StdbPlayer",
+    "StdbWorld",
+    "StdbWorldTime",
+    "StdbItem",
+    "StdbItemDrop",
+    "StdbInventoryItem",
+    "StdbPing",
+    "StdbPath",
+    "StdbClockSync",
+    "StdbZone",
+    "StdbZoneOccupant",
+    "StdbZoneEvent",
+    "StdbPortal",
+    "StdbPortalJump",
+    "StdbRoom",
+    "StdbTeam",
+    "StdbMatch",
+    "StdbMatchScore",
+    "StdbMatchResult",
+    "StdbPlayerStats",
+    "StdbLeaderboardEntry",
+];
+
+/// Register subscriptions for all rows of the global tables, only the objects in the room
+/// with `room_id`, and only the chat messages sent to everyone or, once on a team, delivered
+/// to us. The server only delivers team messages to the team's members. Replaces any previous
+/// subscription.
+pub fn subscribe_to(room_id: u64, team_id: u64) {
+    let mut queries: Vec<String> = GLOBAL_TABLES
+        .iter()
+        .map(|table| format!("SELECT * FROM {}", table))
+        .collect();
+    queries.push(format!(
+        "SELECT * FROM StdbObject WHERE room_id = {}",
+        room_id
+    ));
+    queries.push(format!(
+        "SELECT * FROM StdbChatMessage WHERE recipient_id = 0x{}",
+        hex::encode(chat::EVERYONE)
+    ));
+    if team_id != team::NO_TEAM {
+        // Our identity is known by the time we're put on a team.
+        if let Ok(identity) = spacetimedb_sdk::identity::identity() {
+            queries.push(format!(
+                "SELECT * FROM StdbChatMessage WHERE recipient_id = 0x{}",
+                hex::encode(identity.bytes())
+            ));
+        }
+    }
+
+    let queries: Vec<&str> = queries.iter().map(String::as_str).collect();
+    subscribe(&queries).unwrap();
+}
+
+//#region callbacks
+/// Forwards the SDK callbacks to `uncb_send` as `UncbMessage`s.
+pub fn register_callbacks(uncb_send: UncbSend) {
+    once_on_connect(on_connected(uncb_send.clone()));
+    on_disconnect(on_disconnected(uncb_send.clone()));
+    spacetimedb_sdk::on_subscription_applied(on_subscription_applied(uncb_send.clone()));
+
+    StdbObject::on_insert(on_object_inserted(uncb_send.clone()));
+    StdbObject::on_update(on_object_updated(uncb_send.clone()));
+    StdbObject::on_delete(on_object_deleted(uncb_send.clone()));
+
+    StdbClient::on_insert(on_client_inserted(uncb_send.clone()));
+    StdbClient::on_update(on_client_updated(uncb_send.clone()));
+
+    StdbPlayer::on_insert(on_player_inserted(uncb_send.clone()));
+    StdbPlayer::on_update(on_player_updated(uncb_send.clone()));
+    StdbPlayer::on_delete(on_player_deleted(uncb_send.clone()));
+
+    StdbInventoryItem::on_insert(on_inventory_item_inserted(uncb_send.clone()));
+    StdbInventoryItem::on_update(on_inventory_item_updated(uncb_send.clone()));
+    StdbInventoryItem::on_delete(on_inventory_item_deleted(uncb_send.clone()));
+
+    StdbChatMessage::on_insert(on_chat_message_inserted(uncb_send.clone()));
+
+    StdbZoneEvent::on_insert(on_zone_event_inserted(uncb_send.clone()));
+
+    StdbPortalJump::on_insert(on_portal_jump_inserted(uncb_send.clone()));
+    StdbPortalJump::on_update(on_portal_jump_updated(uncb_send.clone()));
+}
+
+fn on_connected(uncb_send: UncbSend) -> impl FnMut(&Credentials, Address) + Send + 'static {
+    move |creds, address| {
+        uncb_send
+            .unbounded_send(UncbMessage::Connected {
+                creds: creds.clone(),
+                address,
+            })
+            .unwrap();
+    }
+}
+
+/// Saves the credentials of this connection so the next session logs in as the same identity.
+/// Bots skip this, their identities are throwaway.
+pub fn save_credentials_on_connect() {
+    once_on_connect(|creds, _| {
+        if let Err(e) = save_credentials(CREDS_DIR, creds) {
+            eprintln!("Failed to save credentials: {:?}", e);
+        }
+    });
+}
+
+fn on_disconnected(uncb_send: UncbSend) -> impl FnMut() + Send + 'static {
+    move || {
+        eprintln!("Disconnected!");
+        uncb_send.unbounded_send(UncbMessage::Disconnected).unwrap();
+        std::process::exit(0)
+    }
+}
+
+fn on_subscription_applied(uncb_send: UncbSend) -> impl FnMut() + Send + 'static {
+    move || {
+        uncb_send
+            .unbounded_send(UncbMessage::SubscriptionApplied)
+            .unwrap();
+    }
+}
+
+fn on_object_inserted(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbObject, Option<&ReducerEvent>) + Send + 'static {
+    move |object, event| {
+        if let Some(event) = event {
+            uncb_send
+                .unbounded_send(UncbMessage::ObjectInserted {
+                    data: object.clone(),
+                    event: event.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn on_object_updated(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbObject, &StdbObject, Option<&ReducerEvent>) + Send + 'static {
+    move |old, new, event| {
+        if let Some(event) = event {
+            uncb_send
+                .unbounded_send(UncbMessage::ObjectUpdated {
+                    new: new.clone(),
+                    old: old.clone(),
+                    event: event.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn on_object_deleted(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbObject, Option<&ReducerEvent>) + Send + 'static {
+    move |object, event| {
+        if let Some(event) = event {
+            uncb_send
+                .unbounded_send(UncbMessage::ObjectRemoved {
+                    data: object.clone(),
+                    event: event.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn on_client_inserted(
+    _uncb_send: UncbSend,
+) -> impl FnMut(&StdbClient, Option<&ReducerEvent>) + Send + 'static {
+    move |client, _event| {
+        if client.connected {
+            println!(
+                "Client {} connected.",
+                identity_leading_hex(&client.client_id)
+            );
+        }
+    }
+}
+
+fn on_client_updated(
+    mut _uncb_send: UncbSend,
+) -> impl FnMut(&StdbClient, &StdbClient, Option<&ReducerEvent>) + Send + 'static {
+    move |old, new, _event| {
+        if old.connected && !new.connected {
+            println!(
+                "Client {} disconnected.",
+                identity_leading_hex(&new.client_id)
+            );
+        }
+        if !old.connected && new.connected {
+            println!("Client {} connected.", identity_leading_hex(&new.client_id));
+        }
+    }
+}
+
+fn on_player_inserted(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbPlayer, Option<&ReducerEvent>) + Send + 'static {
+    move |player, event| {
+        if let Some(event) = event {
+            info!("UncbMessage::PlayerInserted called");
+            uncb_send
+                .unbounded_send(UncbMessage::PlayerInserted {
+                    data: player.clone(),
+                    event: event.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn on_player_updated(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbPlayer, &StdbPlayer, Option<&ReducerEvent>) + Send + 'static {
+    move |old, new, event| {
+        if let Some(event) = event {
+            info!("UncbMessage::PlayerUpdated called");
+            uncb_send
+                .unbounded_send(UncbMessage::PlayerUpdated {
+                    old: old.clone(),
+                    new: new.clone(),
+                    event: event.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn on_player_deleted(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbPlayer, Option<&ReducerEvent>) + Send + 'static {
+    move |player, event| {
+        info!("UncbMessage::PlayerRemoved called");
+        uncb_send
+            .unbounded_send(UncbMessage::PlayerRemoved {
+                data: player.clone(),
+                event: event.cloned(),
+            })
+            .unwrap();
+    }
+}
+
+fn on_inventory_item_inserted(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbInventoryItem, Option<&ReducerEvent>) + Send + 'static {
+    move |item, event| {
+        if let Some(event) = event {
+            uncb_send
+                .unbounded_send(UncbMessage::InventoryItemInserted {
+                    data: item.clone(),
+                    event: event.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn on_inventory_item_updated(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbInventoryItem, &StdbInventoryItem, Option<&ReducerEvent>) + Send + 'static {
+    move |old, new, event| {
+        if let Some(event) = event {
+            uncb_send
+                .unbounded_send(UncbMessage::InventoryItemUpdated {
+                    old: old.clone(),
+                    new: new.clone(),
+                    event: event.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn on_inventory_item_deleted(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbInventoryItem, Option<&ReducerEvent>) + Send + 'static {
+    move |item, event| {
+        if let Some(event) = event {
+            uncb_send
+                .unbounded_send(UncbMessage::InventoryItemRemoved {
+                    data: item.clone(),
+                    event: event.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn on_chat_message_inserted(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbChatMessage, Option<&ReducerEvent>) + Send + 'static {
+    move |message, event| {
+        if let Some(event) = event {
+            uncb_send
+                .unbounded_send(UncbMessage::ChatMessageInserted {
+                    data: message.clone(),
+                    event: event.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn on_zone_event_inserted(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbZoneEvent, Option<&ReducerEvent>) + Send + 'static {
+    move |zone_event, event| {
+        if let Some(event) = event {
+            uncb_send
+                .unbounded_send(UncbMessage::ZoneEventInserted {
+                    data: zone_event.clone(),
+                    event: event.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn on_portal_jump_inserted(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbPortalJump, Option<&ReducerEvent>) + Send + 'static {
+    move |jump, event| {
+        if let Some(event) = event {
+            uncb_send
+                .unbounded_send(UncbMessage::PortalJumped {
+                    data: jump.clone(),
+                    event: event.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn on_portal_jump_updated(
+    uncb_send: UncbSend,
+) -> impl FnMut(&StdbPortalJump, &StdbPortalJump, Option<&ReducerEvent>) + Send + 'static {
+    move |_old, new, event| {
+        if let Some(event) = event {
+            uncb_send
+                .unbounded_send(UncbMessage::PortalJumped {
+                    data: new.clone(),
+                    event: event.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+fn identity_leading_hex(id: &Identity) -> String {
+    hex::encode(&id.bytes()[0..8])
+}
+//#endregion callbacks
+
+//#region helpers
+pub fn bool_to_f32(v: bool) -> f32 {
+    if v {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+pub fn nan_to_zero(v: f32) -> f32 {
+    if v.is_nan() {
+        0.0
+    } else {
+        v
+    }
+}
+
+pub fn vec2_nan_to_zero(v: Vec2) -> Vec2 {
+    Vec2 {
+        x: nan_to_zero(v.x),
+        y: nan_to_zero(v.y),
+    }
+}
+
+pub fn stdb_to_vec2(v: &StdbVector2) -> Vec2 {
+    Vec2 { x: v.x, y: v.y }
+}
+
+/// Whether `client_id` is this client. Always false while replaying, since there's no connection.
+pub fn is_local(client_id: &Identity) -> bool {
+    spacetimedb_sdk::identity::identity().is_ok_and(|identity| identity == *client_id)
+}
+//#endregion helpers
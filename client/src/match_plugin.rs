@@ -0,0 +1,179 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    ecs::{
+        query::With,
+        system::{Commands, Query, Res},
+    },
+    hierarchy::BuildChildren,
+    render::{color::Color, view::Visibility},
+    text::{Text, TextAlignment, TextStyle},
+    ui::{
+        node_bundles::{NodeBundle, TextBundle},
+        AlignItems, FlexDirection, JustifyContent, PositionType, Style, UiRect, Val,
+    },
+};
+use spacetimedb_sdk::table::TableType;
+
+use crate::{
+    identity_leading_hex,
+    matches::{MatchStatusText, Scoreboard, ScoreboardText},
+    room::CurrentRoom,
+    StdbMatch, StdbMatchResult, StdbMatchScore, StdbMatchState, StdbPlayer,
+};
+
+pub struct MatchPlugin;
+impl Plugin for MatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (init_match_ui,))
+            .add_systems(Update, (refresh_match_status, refresh_scoreboard));
+    }
+}
+
+/// Spawns the match status along the top of the screen and the hidden scoreboard in the middle.
+fn init_match_ui(mut c: Commands) {
+    let style = TextStyle {
+        font_size: 24.0,
+        color: Color::WHITE,
+        ..Default::default()
+    };
+
+    c.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .with_children(|parent| {
+        parent.spawn((MatchStatusText, TextBundle::from_section("", style.clone())));
+    });
+
+    c.spawn((
+        Scoreboard,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+    ))
+    .with_children(|parent| {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(20.0)),
+                    flex_direction: FlexDirection::Column,
+                    ..Default::default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                ..Default::default()
+            })
+            .with_children(|parent| {
+                parent.spawn((
+                    ScoreboardText,
+                    TextBundle::from_section("", style).with_text_alignment(TextAlignment::Center),
+                ));
+            });
+    });
+}
+
+/// Shows the state of the match in the current room: how many players it's waiting for,
+/// the countdown, or the time left and the local player's score.
+fn refresh_match_status(current: Res<CurrentRoom>, mut q: Query<&mut Text, With<MatchStatusText>>) {
+    let status = match StdbMatch::filter_by_room_id(current.room_id) {
+        Some(stdb_match) => match stdb_match.state {
+            StdbMatchState::Waiting => {
+                let players = StdbPlayer::iter()
+                    .filter(|player| player.room_id == current.room_id)
+                    .count();
+                format!(
+                    "Waiting for players ({}/{})",
+                    players, stdb_match.min_players
+                )
+            }
+            StdbMatchState::Countdown => {
+                format!("Match starts in {}", stdb_match.seconds_remaining)
+            }
+            StdbMatchState::Running => format!(
+                "{}:{:02}  Score: {}",
+                stdb_match.seconds_remaining / 60,
+                stdb_match.seconds_remaining % 60,
+                local_score()
+            ),
+            StdbMatchState::Finished => "Match over".to_string(),
+        },
+        // There's no match in the lobby.
+        None => String::new(),
+    };
+
+    for mut text in &mut q {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
+
+/// Shows the results of the match that just finished in the current room until the next
+/// one starts waiting for players.
+fn refresh_scoreboard(
+    current: Res<CurrentRoom>,
+    mut q_scoreboard: Query<&mut Visibility, With<Scoreboard>>,
+    mut q_text: Query<&mut Text, With<ScoreboardText>>,
+) {
+    let finished = StdbMatch::filter_by_room_id(current.room_id)
+        .filter(|stdb_match| matches!(stdb_match.state, StdbMatchState::Finished));
+
+    for mut visibility in &mut q_scoreboard {
+        *visibility = if finished.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    let Some(stdb_match) = finished else {
+        return;
+    };
+
+    let mut results: Vec<StdbMatchResult> = StdbMatchResult::iter()
+        .filter(|result| {
+            result.room_id == stdb_match.room_id && result.match_number == stdb_match.match_number
+        })
+        .collect();
+    results.sort_by_key(|result| result.rank);
+
+    let mut scoreboard = format!("Match {} Results", stdb_match.match_number);
+    if results.is_empty() {
+        scoreboard.push_str("\nNobody scored");
+    }
+    for result in results {
+        scoreboard.push_str(&format!(
+            "\n{}. {}  {}",
+            result.rank,
+            identity_leading_hex(&result.client_id),
+            result.score
+        ));
+    }
+
+    for mut text in &mut q_text {
+        if text.sections[0].value != scoreboard {
+            text.sections[0].value = scoreboard.clone();
+        }
+    }
+}
+
+fn local_score() -> u32 {
+    spacetimedb_sdk::identity::identity()
+        .ok()
+        .and_then(StdbMatchScore::filter_by_client_id)
+        .map_or(0, |score| score.score)
+}
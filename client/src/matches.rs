@@ -0,0 +1,10 @@
+use bevy::ecs::component::Component;
+
+#[derive(Component)]
+pub struct MatchStatusText;
+
+#[derive(Component)]
+pub struct Scoreboard;
+
+#[derive(Component)]
+pub struct ScoreboardText;
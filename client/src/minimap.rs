@@ -0,0 +1,30 @@
+use bevy::{
+    ecs::{component::Component, system::Resource},
+    math::Vec2,
+    render::color::Color,
+    time::{Timer, TimerMode},
+};
+
+/// Size of the minimap on screen in pixels.
+pub const MINIMAP_SIZE: Vec2 = Vec2 { x: 200.0, y: 150.0 };
+/// Size of the marker drawn for every object in pixels.
+pub const MINIMAP_MARKER_SIZE: f32 = 5.0;
+/// How far from the local player another player's ping in the same room is still shown.
+pub const PING_RADIUS: f32 = 800.0;
+
+pub const MINIMAP_SELF_COLOR: Color = Color::LIME_GREEN;
+pub const MINIMAP_PLAYER_COLOR: Color = Color::CYAN;
+pub const MINIMAP_PING_COLOR: Color = Color::RED;
+
+#[derive(Component)]
+pub struct Minimap;
+
+/// Limits how often the minimap markers are rebuilt.
+#[derive(Resource)]
+pub struct MinimapTimer(pub Timer);
+
+impl Default for MinimapTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(0.1, TimerMode::Repeating))
+    }
+}
@@ -0,0 +1,179 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    ecs::{
+        entity::Entity,
+        query::{Changed, With},
+        system::{Commands, Query, Res, ResMut},
+    },
+    hierarchy::{BuildChildren, DespawnRecursiveExt},
+    math::{Rect, Vec2},
+    render::color::Color,
+    time::Time,
+    ui::{
+        node_bundles::NodeBundle, Interaction, Overflow, PositionType, RelativeCursorPosition,
+        Style, Val,
+    },
+};
+use spacetimedb_sdk::table::TableType;
+
+use crate::{
+    diagnostics::NetworkStats,
+    minimap::{
+        Minimap, MinimapTimer, MINIMAP_MARKER_SIZE, MINIMAP_PING_COLOR, MINIMAP_PLAYER_COLOR,
+        MINIMAP_SELF_COLOR, MINIMAP_SIZE, PING_RADIUS,
+    },
+    object::{ObjectRegistry, OBJECT_KIND_PLAYER},
+    ping, stdb_to_vec2,
+    world::world_bounds,
+    StdbObject, StdbPing, StdbPlayer, StdbVector2,
+};
+
+pub struct MinimapPlugin;
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MinimapTimer>()
+            .add_systems(Startup, (init_minimap,))
+            .add_systems(Update, (draw_minimap, click_minimap));
+    }
+}
+
+/// Spawns the minimap in the bottom right corner of the screen.
+fn init_minimap(mut c: Commands) {
+    c.spawn((
+        Minimap,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                right: Val::Px(10.0),
+                width: Val::Px(MINIMAP_SIZE.x),
+                height: Val::Px(MINIMAP_SIZE.y),
+                overflow: Overflow::clip(),
+                ..Default::default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            ..Default::default()
+        },
+        Interaction::default(),
+        RelativeCursorPosition::default(),
+    ));
+}
+
+/// Rebuilds the minimap markers from the mirrored `StdbObject`, `StdbPlayer` and `StdbPing` rows.
+/// The local player is drawn last so it's never hidden behind another marker.
+fn draw_minimap(
+    mut c: Commands,
+    time: Res<Time>,
+    mut timer: ResMut<MinimapTimer>,
+    registry: Res<ObjectRegistry>,
+    q: Query<Entity, With<Minimap>>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Some(bounds) = world_bounds() else {
+        return;
+    };
+
+    let local_object = spacetimedb_sdk::identity::identity()
+        .ok()
+        .and_then(StdbPlayer::filter_by_client_id)
+        .and_then(|player| StdbObject::filter_by_object_id(player.object_id));
+
+    let mut markers: Vec<(Vec2, Color, f32)> = Vec::new();
+    for object in StdbObject::iter() {
+        if local_object
+            .as_ref()
+            .is_some_and(|local| local.object_id == object.object_id)
+        {
+            continue;
+        }
+
+        let color = if object.kind == OBJECT_KIND_PLAYER {
+            MINIMAP_PLAYER_COLOR
+        } else {
+            registry.get(&object.kind).color
+        };
+        markers.push((stdb_to_vec2(&object.position), color, MINIMAP_MARKER_SIZE));
+    }
+
+    if let Some(local) = local_object {
+        let local_position = stdb_to_vec2(&local.position);
+        for stdb_ping in StdbPing::iter() {
+            let ping_position = stdb_to_vec2(&stdb_ping.position);
+            if stdb_ping.room_id == local.room_id
+                && ping_position.distance(local_position) <= PING_RADIUS
+            {
+                markers.push((ping_position, MINIMAP_PING_COLOR, MINIMAP_MARKER_SIZE * 2.0));
+            }
+        }
+        markers.push((
+            local_position,
+            MINIMAP_SELF_COLOR,
+            MINIMAP_MARKER_SIZE * 1.5,
+        ));
+    }
+
+    for entity in &q {
+        c.entity(entity)
+            .despawn_descendants()
+            .with_children(|parent| {
+                for (position, color, size) in &markers {
+                    let marker = world_to_minimap(*position, bounds);
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(marker.x - size / 2.0),
+                            top: Val::Px(marker.y - size / 2.0),
+                            width: Val::Px(*size),
+                            height: Val::Px(*size),
+                            ..Default::default()
+                        },
+                        background_color: (*color).into(),
+                        ..Default::default()
+                    });
+                }
+            });
+    }
+}
+
+/// Clicking the minimap pings the clicked location for every player nearby.
+fn click_minimap(
+    q: Query<(&Interaction, &RelativeCursorPosition), (Changed<Interaction>, With<Minimap>)>,
+    stats: Res<NetworkStats>,
+) {
+    for (interaction, cursor) in &q {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let (Some(normalized), Some(bounds)) = (cursor.normalized, world_bounds()) else {
+            continue;
+        };
+
+        let position = minimap_to_world(normalized, bounds);
+        stats.sent.count_reducer_call();
+        ping(StdbVector2 {
+            x: position.x,
+            y: position.y,
+        });
+    }
+}
+
+/// Converts a world position into pixels from the top left corner of the minimap.
+fn world_to_minimap(position: Vec2, bounds: Rect) -> Vec2 {
+    let normalized = (position - bounds.min) / bounds.size();
+    Vec2 {
+        x: normalized.x * MINIMAP_SIZE.x,
+        y: (1.0 - normalized.y) * MINIMAP_SIZE.y,
+    }
+}
+
+/// Converts a position relative to the minimap, (0, 0) top left and (1, 1) bottom right,
+/// into a world position.
+fn minimap_to_world(normalized: Vec2, bounds: Rect) -> Vec2 {
+    bounds.min
+        + Vec2 {
+            x: normalized.x,
+            y: 1.0 - normalized.y,
+        } * bounds.size()
+}
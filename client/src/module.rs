@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use bevy::{ecs::system::Resource, math::Rect};
+use spacetimedb_sdk::{identity::Identity, table::TableType};
+
+use crate::{
+    diagnostics::SentCounter, world::world_bounds, StdbObject, StdbPath, StdbPlayer, StdbVector2,
+};
+
+/// Everything the player systems read from and call on the module. `SdkModule` goes through
+/// the SDK's connection and client cache, tests swap in a `FakeModule` to run without a server.
+pub trait ModuleApi: Send + Sync {
+    /// `None` until connected, and while replaying.
+    fn local_identity(&self) -> Option<Identity>;
+    fn players(&self) -> Vec<StdbPlayer>;
+    fn player(&self, object_id: u64) -> Option<StdbPlayer>;
+    fn object(&self, object_id: u64) -> Option<StdbObject>;
+    fn world_bounds(&self) -> Option<Rect>;
+    /// Whether the server is walking the object along a path, see `move_to`.
+    fn has_path(&self, object_id: u64) -> bool;
+
+    fn create_player(&self);
+    fn update_player_pos(&self, position: StdbVector2);
+    fn move_to(&self, destination: StdbVector2);
+    fn set_appearance(&self, color: String, skin: String);
+
+    fn is_local(&self, client_id: &Identity) -> bool {
+        self.local_identity().as_ref() == Some(client_id)
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct Module(pub Arc<dyn ModuleApi>);
+
+/// Counts every reducer call it makes into `sent`, the counter of `NetworkStats`.
+pub struct SdkModule {
+    pub sent: SentCounter,
+}
+
+impl ModuleApi for SdkModule {
+    fn local_identity(&self) -> Option<Identity> {
+        spacetimedb_sdk::identity::identity().ok()
+    }
+
+    fn players(&self) -> Vec<StdbPlayer> {
+        StdbPlayer::iter().collect()
+    }
+
+    fn player(&self, object_id: u64) -> Option<StdbPlayer> {
+        StdbPlayer::filter_by_object_id(object_id)
+    }
+
+    fn object(&self, object_id: u64) -> Option<StdbObject> {
+        StdbObject::filter_by_object_id(object_id)
+    }
+
+    fn world_bounds(&self) -> Option<Rect> {
+        world_bounds()
+    }
+
+    fn has_path(&self, object_id: u64) -> bool {
+        StdbPath::filter_by_object_id(object_id).is_some()
+    }
+
+    fn create_player(&self) {
+        self.sent.count_reducer_call();
+        crate::create_player();
+    }
+
+    fn update_player_pos(&self, position: StdbVector2) {
+        self.sent.track_move(&position);
+        crate::update_player_pos(position);
+    }
+
+    fn move_to(&self, destination: StdbVector2) {
+        self.sent.count_reducer_call();
+        crate::move_to(destination);
+    }
+
+    fn set_appearance(&self, color: String, skin: String) {
+        self.sent.count_reducer_call();
+        crate::set_appearance(color, skin);
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::AssetServer,
+    ecs::{bundle::Bundle, component::Component, system::Resource},
+    math::Vec2,
+    render::color::Color,
+    sprite::{Sprite, SpriteBundle},
+    transform::components::Transform,
+};
+
+use crate::{clock::ServerClock, stdb_to_vec2, StdbObject};
+
+/// Objects of this kind are rendered by the `PlayerPlugin` instead of the `ObjectPlugin`.
+pub const OBJECT_KIND_PLAYER: &str = "player";
+/// How long after its last move an object keeps going along its velocity at most, so one
+/// that stopped without us hearing about it yet doesn't drift off.
+pub const MAX_EXTRAPOLATION_SECS: f32 = 0.25;
+
+#[derive(Component)]
+pub struct WorldObject {
+    pub data: StdbObject,
+}
+
+#[derive(Bundle)]
+pub struct ObjectBundle {
+    pub object: WorldObject,
+    pub sprite_bundle: SpriteBundle,
+}
+
+/// Describes how every object of a single kind is drawn.
+#[derive(Clone)]
+pub struct ObjectSprite {
+    pub color: Color,
+    pub size: Vec2,
+    /// Path of a texture inside the `assets` folder, the sprite is a flat colored square without one.
+    pub texture: Option<&'static str>,
+    /// Depth of the sprite, objects with a higher `z` are drawn on top.
+    pub z: f32,
+}
+
+/// Maps `StdbObject::kind` to the sprite used to draw objects of that kind.
+#[derive(Resource)]
+pub struct ObjectRegistry {
+    pub sprites: HashMap<String, ObjectSprite>,
+    /// Used for any kind that hasn't been registered.
+    pub fallback: ObjectSprite,
+}
+
+impl ObjectRegistry {
+    pub fn get(&self, kind: &str) -> &ObjectSprite {
+        self.sprites.get(kind).unwrap_or(&self.fallback)
+    }
+}
+
+impl Default for ObjectRegistry {
+    fn default() -> Self {
+        let sprites = [
+            (
+                "item",
+                ObjectSprite {
+                    color: Color::GOLD,
+                    size: Vec2 { x: 20.0, y: 20.0 },
+                    texture: None,
+                    z: -1.0,
+                },
+            ),
+            (
+                "rock",
+                ObjectSprite {
+                    color: Color::GRAY,
+                    size: Vec2 { x: 60.0, y: 45.0 },
+                    texture: None,
+                    z: -2.0,
+                },
+            ),
+            (
+                "tree",
+                ObjectSprite {
+                    color: Color::DARK_GREEN,
+                    size: Vec2 { x: 70.0, y: 90.0 },
+                    texture: None,
+                    z: 1.0,
+                },
+            ),
+            (
+                "portal",
+                ObjectSprite {
+                    color: Color::PURPLE,
+                    size: Vec2 { x: 60.0, y: 60.0 },
+                    texture: None,
+                    z: -2.0,
+                },
+            ),
+            (
+                "slime",
+                ObjectSprite {
+                    color: Color::SEA_GREEN,
+                    size: Vec2 { x: 30.0, y: 25.0 },
+                    texture: None,
+                    z: 0.0,
+                },
+            ),
+            (
+                "dog",
+                ObjectSprite {
+                    color: Color::ORANGE,
+                    size: Vec2 { x: 40.0, y: 25.0 },
+                    texture: None,
+                    z: 0.0,
+                },
+            ),
+            (
+                "rabbit",
+                ObjectSprite {
+                    color: Color::BEIGE,
+                    size: Vec2 { x: 20.0, y: 20.0 },
+                    texture: None,
+                    z: 0.0,
+                },
+            ),
+            (
+                "guard",
+                ObjectSprite {
+                    color: Color::MAROON,
+                    size: Vec2 { x: 45.0, y: 50.0 },
+                    texture: None,
+                    z: 0.0,
+                },
+            ),
+        ];
+
+        Self {
+            sprites: sprites
+                .into_iter()
+                .map(|(kind, sprite)| (kind.to_string(), sprite))
+                .collect(),
+            fallback: ObjectSprite {
+                color: Color::FUCHSIA,
+                size: Vec2 { x: 30.0, y: 30.0 },
+                texture: None,
+                z: 0.0,
+            },
+        }
+    }
+}
+
+/// Where `object` most likely is by now, having kept its velocity since it last moved.
+/// Until `clock` is synced it's drawn where it last was.
+pub fn extrapolated_position(object: &StdbObject, clock: Option<&ServerClock>) -> Vec2 {
+    let position = stdb_to_vec2(&object.position);
+    let Some(now) = clock.and_then(ServerClock::server_now) else {
+        return position;
+    };
+
+    let secs = now.saturating_sub(object.moved_at_micros) as f32 / 1_000_000.0;
+    position + stdb_to_vec2(&object.velocity) * secs.min(MAX_EXTRAPOLATION_SECS)
+}
+
+/// Sprites are drawn facing right, so ones facing left are mirrored instead of turned upside down.
+pub fn faces_left(facing: f32) -> bool {
+    facing.cos() < 0.0
+}
+
+impl ObjectBundle {
+    pub fn new(object: WorldObject, registry: &ObjectRegistry, assets: &AssetServer) -> Self {
+        let sprite = registry.get(&object.data.kind);
+        let transform =
+            Transform::from_xyz(object.data.position.x, object.data.position.y, sprite.z);
+        let flip_x = faces_left(object.data.facing);
+
+        Self {
+            object,
+            sprite_bundle: SpriteBundle {
+                sprite: Sprite {
+                    color: sprite.color,
+                    custom_size: Some(sprite.size),
+                    flip_x,
+                    ..Default::default()
+                },
+                texture: match sprite.texture {
+                    Some(path) => assets.load(path),
+                    None => Default::default(),
+                },
+                transform,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{clock::local_micros, StdbVector2};
+
+    fn moving_object(moved_at_micros: u64) -> StdbObject {
+        StdbObject {
+            object_id: 1,
+            name: "npc".to_string(),
+            kind: "npc".to_string(),
+            room_id: 0,
+            position: StdbVector2 { x: 10.0, y: 0.0 },
+            velocity: StdbVector2 { x: 100.0, y: 0.0 },
+            facing: 0.0,
+            moved_at_micros,
+        }
+    }
+
+    #[test]
+    fn stays_put_until_the_clock_is_synced() {
+        let object = moving_object(0);
+
+        assert_eq!(extrapolated_position(&object, None), Vec2::new(10.0, 0.0));
+        assert_eq!(
+            extrapolated_position(&object, Some(&ServerClock::default())),
+            Vec2::new(10.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn keeps_moving_for_a_while_after_its_last_update() {
+        let now = local_micros();
+        let mut clock = ServerClock::default();
+        clock.add_sample(now, now, now);
+
+        let position = extrapolated_position(&moving_object(now - 5_000_000), Some(&clock));
+        assert_eq!(
+            position,
+            Vec2::new(10.0 + 100.0 * MAX_EXTRAPOLATION_SECS, 0.0)
+        );
+    }
+
+    #[test]
+    fn faces_left_only_when_heading_left() {
+        assert!(!faces_left(0.0));
+        assert!(faces_left(std::f32::consts::PI));
+        assert!(!faces_left(1.0));
+        assert!(faces_left(-2.0));
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use bevy::{
+    app::{App, Plugin, Update},
+    asset::AssetServer,
+    ecs::{
+        entity::Entity,
+        event::EventReader,
+        schedule::IntoSystemConfigs,
+        system::{Commands, Query, Res},
+    },
+    log::info,
+    sprite::Sprite,
+    transform::components::Transform,
+};
+use spacetimedb_sdk::table::TableType;
+
+use crate::{
+    clock::ServerClock,
+    object::{
+        extrapolated_position, faces_left, ObjectBundle, ObjectRegistry, WorldObject,
+        OBJECT_KIND_PLAYER,
+    },
+    uncb_receiver::{UncbEvent, UncbMessage},
+    StdbObject,
+};
+
+pub struct ObjectPlugin;
+impl Plugin for ObjectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ObjectRegistry>().add_systems(
+            Update,
+            (
+                init_objects,
+                refresh_objects,
+                update_objects,
+                move_objects.after(update_objects),
+                remove_objects,
+            ),
+        );
+    }
+}
+
+/// Waits for the `UncbMessage::SubscriptionApplied` message and makes the spawned objects
+/// match the client cache, since rows from a subscription don't send insert messages.
+fn init_objects(
+    mut c: Commands,
+    q: Query<(Entity, &WorldObject)>,
+    registry: Res<ObjectRegistry>,
+    assets: Res<AssetServer>,
+    mut er: EventReader<UncbEvent>,
+) {
+    for ev in er.read() {
+        match &ev.message {
+            UncbMessage::SubscriptionApplied => {
+                let cached: HashSet<u64> = StdbObject::iter()
+                    .filter(|object| object.kind != OBJECT_KIND_PLAYER)
+                    .map(|object| object.object_id)
+                    .collect();
+
+                let mut spawned: HashSet<u64> = HashSet::new();
+                for (entity, object) in q.iter() {
+                    if cached.contains(&object.data.object_id) {
+                        spawned.insert(object.data.object_id);
+                    } else {
+                        c.entity(entity).despawn();
+                    }
+                }
+
+                for stdb_object in StdbObject::iter() {
+                    if stdb_object.kind == OBJECT_KIND_PLAYER
+                        || spawned.contains(&stdb_object.object_id)
+                    {
+                        continue;
+                    }
+
+                    c.spawn(ObjectBundle::new(
+                        WorldObject { data: stdb_object },
+                        &registry,
+                        &assets,
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Listens for the `UncbMessage::ObjectInserted` message and spawns an `ObjectBundle` for
+/// every object that isn't a player.
+fn refresh_objects(
+    mut c: Commands,
+    registry: Res<ObjectRegistry>,
+    assets: Res<AssetServer>,
+    mut er: EventReader<UncbEvent>,
+) {
+    for ev in er.read() {
+        match &ev.message {
+            UncbMessage::ObjectInserted { data, event: _ } => {
+                if data.kind == OBJECT_KIND_PLAYER {
+                    continue;
+                }
+
+                info!("Spawned object: {}", data.object_id);
+                c.spawn(ObjectBundle::new(
+                    WorldObject { data: data.clone() },
+                    &registry,
+                    &assets,
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Listens for the `UncbMessage::ObjectUpdated` message and updates the matching object,
+/// `move_objects` then moves it.
+fn update_objects(mut q: Query<&mut WorldObject>, mut er: EventReader<UncbEvent>) {
+    for ev in er.read() {
+        match &ev.message {
+            UncbMessage::ObjectUpdated {
+                old: _,
+                new,
+                event: _,
+            } => {
+                for mut object in q.iter_mut() {
+                    if object.data.object_id == new.object_id {
+                        object.data = new.clone();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Keeps every object moving along its velocity between updates, mirrored when it faces
+/// left like players are.
+fn move_objects(
+    mut q: Query<(&WorldObject, &mut Transform, &mut Sprite)>,
+    clock: Option<Res<ServerClock>>,
+) {
+    for (object, mut transform, mut sprite) in &mut q {
+        let position = extrapolated_position(&object.data, clock.as_deref());
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        sprite.flip_x = faces_left(object.data.facing);
+    }
+}
+
+/// Listens for the `UncbMessage::ObjectRemoved` message and despawns the matching object.
+fn remove_objects(
+    mut c: Commands,
+    q: Query<(Entity, &WorldObject)>,
+    mut er: EventReader<UncbEvent>,
+) {
+    for ev in er.read() {
+        match &ev.message {
+            UncbMessage::ObjectRemoved { data, event: _ } => {
+                for (entity, object) in q.iter() {
+                    if object.data.object_id == data.object_id {
+                        info!("Object removed: {}", data.object_id);
+                        c.entity(entity).despawn();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
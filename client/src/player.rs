@@ -1,10 +1,11 @@
 use bevy::{
+    asset::AssetServer,
     ecs::{bundle::Bundle, component::Component},
     math::Vec2,
     sprite::{Sprite, SpriteBundle},
 };
 
-use crate::StdbPlayer;
+use crate::{appearance::skin_texture, team::player_color, StdbPlayer};
 
 #[derive(Component)]
 pub struct Player {
@@ -19,15 +20,27 @@ pub struct PlayerBundle {
 
 pub const PLAYER_SPEED: f32 = 11.0;
 
+/// On the local player while the server walks them to where they clicked. Their position
+/// comes from the server instead of being sent to it, until they move by hand or arrive.
+#[derive(Component, Default)]
+pub struct Navigating {
+    /// Whether the path has shown up yet, before that it not being there doesn't mean we arrived.
+    pub seen_path: bool,
+}
+
 impl PlayerBundle {
-    pub fn new(player: Player) -> Self {
+    pub fn new(player: Player, assets: &AssetServer) -> Self {
+        let appearance = player.data.appearance.clone();
+        let color = player_color(&appearance.color, player.data.team_id);
         Self {
             player,
             sprite_bundle: SpriteBundle {
                 sprite: Sprite {
+                    color,
                     custom_size: Some(Vec2 { x: 50.0, y: 50.0 }),
                     ..Default::default()
                 },
+                texture: skin_texture(&appearance.skin, assets),
                 ..Default::default()
             },
         }
@@ -1,50 +1,90 @@
 use bevy::{
     app::{App, Plugin, Startup, Update},
+    asset::{AssetServer, Handle},
     ecs::{
         entity::Entity,
         event::EventReader,
         query::With,
-        system::{Commands, Query},
+        schedule::{apply_deferred, IntoSystemConfigs},
+        system::{Commands, Query, Res},
     },
-    input::keyboard::KeyCode,
+    input::gamepad::{GamepadConnection, GamepadConnectionEvent, Gamepads},
     log::info,
-    transform::components::Transform,
+    math::Vec2,
+    render::{camera::Camera, texture::Image},
+    sprite::Sprite,
+    transform::components::{GlobalTransform, Transform},
+    window::{PrimaryWindow, Window},
 };
 use leafwing_input_manager::{action_state::ActionState, input_map::InputMap, InputManagerBundle};
-use spacetimedb_sdk::table::TableType;
 
 use crate::{
-    actions::{get_input_vector, GameActions},
-    create_player, identity_leading_hex,
-    player::{Player, PlayerBundle, PLAYER_SPEED},
+    actions::{get_input_vector, input_map, GameActions},
+    appearance::{next_allowed, skin_texture, ALLOWED_COLORS, ALLOWED_SKINS},
+    camera::MainCamera,
+    clock::ServerClock,
+    identity_leading_hex,
+    keybindings::Keybindings,
+    module::Module,
+    object::{extrapolated_position, faces_left},
+    player::{Navigating, Player, PlayerBundle, PLAYER_SPEED},
+    room::CurrentRoom,
+    team::player_color,
     uncb_receiver::{UncbEvent, UncbMessage},
-    update_player_pos, vec2_nan_to_zero, StdbObject, StdbPlayer,
+    zone::CurrentZones,
+    ReducerEvent, StdbPlayer, StdbVector2,
 };
 
 pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (create_player,)).add_systems(
+        app.add_systems(Startup, (join_game,)).add_systems(
             Update,
             (
                 refresh_players,
-                update_players,
+                // A click's `Navigating` has to be in place before `update_players` would
+                // upload a position, which cancels the fresh path on the server.
+                (click_to_move, apply_deferred, update_players).chain(),
                 init_players,
                 remove_players,
+                move_players,
+                update_appearances,
+                change_appearance,
+                snap_local_player,
+                assign_gamepad,
             ),
         );
     }
 }
 
-/// Listens for the `UncbMessage::ObjectRemoved` message and removes the corresponding player's bundle with the same `object_id` locally.
-fn remove_players(mut c: Commands, q: Query<(Entity, &Player)>, mut er: EventReader<UncbEvent>) {
+fn join_game(module: Res<Module>) {
+    module.0.create_player();
+}
+
+/// Listens for the `UncbMessage::PlayerRemoved` message and despawns the player with the same `object_id` locally.
+fn remove_players(
+    mut c: Commands,
+    q: Query<(Entity, &Player)>,
+    module: Res<Module>,
+    mut er: EventReader<UncbEvent>,
+) {
     for ev in er.read() {
         match &ev.message {
-            UncbMessage::PlayerRemoved { data } => {
+            UncbMessage::PlayerRemoved { data, event } => {
                 info!("Player removed: {}", data.object_id);
+                // Our own player is only deleted while we're connected when an admin kicks or bans us.
+                if module.0.is_local(&data.client_id)
+                    && matches!(
+                        event,
+                        Some(ReducerEvent::KickPlayer(_)) | Some(ReducerEvent::BanPlayer(_))
+                    )
+                {
+                    eprintln!("Removed from the game by an admin!");
+                    std::process::exit(0)
+                }
                 for (entity, player) in q.iter() {
                     if player.data.object_id == data.object_id {
-                        c.entity(entity).remove::<PlayerBundle>();
+                        c.entity(entity).despawn();
                     }
                 }
             }
@@ -57,58 +97,112 @@ fn remove_players(mut c: Commands, q: Query<(Entity, &Player)>, mut er: EventRea
 /// For every player within a query it checks if the player has an input manager, if it does
 /// then it knows we have the client, now we can update it's movement, otherwise, we know we
 /// have another player from the database, so instead it reads data from the database and updates those players.
+/// Other players keep moving along their velocity between updates, and every player faces
+/// the way they're going. While the local player is `Navigating` they're moved like the others.
 fn update_players(
+    mut c: Commands,
     mut q: Query<
         (
+            Entity,
             Option<&ActionState<GameActions>>,
+            Option<&mut Navigating>,
             &mut Transform,
+            &mut Sprite,
             &mut Player,
         ),
         With<Player>,
     >,
+    module: Res<Module>,
+    clock: Option<Res<ServerClock>>,
+    zones: Option<Res<CurrentZones>>,
 ) {
-    for (action_state, mut transform, player) in &mut q {
+    // Zones like roads and swamps change how fast the local player walks.
+    let speed = PLAYER_SPEED * zones.map_or(1.0, |zones| zones.speed_multiplier());
+    for (entity, action_state, navigating, mut transform, mut sprite, player) in &mut q {
         // We have a handle to the local player.
         if let Some(action_state) = action_state {
             // Handle input and update transform locally.
-            let input_vector = vec2_nan_to_zero(get_input_vector(action_state).normalize());
-            transform.translation.x += input_vector.x * PLAYER_SPEED;
-            transform.translation.y += input_vector.y * PLAYER_SPEED;
+            let input_vector = get_input_vector(action_state);
+            // The server walks us until we move by hand or its path is done.
+            if let Some(mut navigating) = navigating {
+                let walking = module.0.has_path(player.data.object_id);
+                navigating.seen_path |= walking;
+                if input_vector != Vec2::ZERO || (navigating.seen_path && !walking) {
+                    c.entity(entity).remove::<Navigating>();
+                } else {
+                    if let Some(stdb_object) = module.0.object(player.data.object_id) {
+                        let position = extrapolated_position(&stdb_object, clock.as_deref());
+                        transform.translation.x = position.x;
+                        transform.translation.y = position.y;
+                        sprite.flip_x = faces_left(stdb_object.facing);
+                    }
+                    continue;
+                }
+            }
+            if input_vector.x != 0.0 {
+                sprite.flip_x = input_vector.x < 0.0;
+            }
+            transform.translation.x += input_vector.x * speed;
+            transform.translation.y += input_vector.y * speed;
+            // Stay inside the world, the server would clamp us anyway.
+            if let Some(bounds) = module.0.world_bounds() {
+                transform.translation.x = transform.translation.x.clamp(bounds.min.x, bounds.max.x);
+                transform.translation.y = transform.translation.y.clamp(bounds.min.y, bounds.max.y);
+            }
             // Then sync to the database.
-            update_player_pos(crate::StdbVector2 {
+            module.0.update_player_pos(crate::StdbVector2 {
                 x: transform.translation.x,
                 y: transform.translation.y,
             })
         }
         // We have a handle to an online player.
         else {
-            // Read from database and update transform.
-            let stdb_object = StdbObject::filter_by_object_id(player.data.object_id);
-            let position = stdb_object.unwrap().position;
-            transform.translation.x = position.x;
-            transform.translation.y = position.y;
+            // Read from database and update transform, the object may already be gone
+            // if the player just left our room.
+            if let Some(stdb_object) = module.0.object(player.data.object_id) {
+                let position = extrapolated_position(&stdb_object, clock.as_deref());
+                transform.translation.x = position.x;
+                transform.translation.y = position.y;
+                sprite.flip_x = faces_left(stdb_object.facing);
+            }
         }
     }
 }
 
-/// Waits for the `UncbMessage::Connected` message in order to spawn all
-/// players that were in the database before connection.
-fn init_players(mut c: Commands, mut er: EventReader<UncbEvent>) {
+/// Waits for the `UncbMessage::SubscriptionApplied` message and makes the spawned players
+/// match the players in the current room, since players from a subscription don't send
+/// insert messages. This happens on connection and again whenever we change rooms.
+fn init_players(
+    mut c: Commands,
+    q: Query<(Entity, &Player)>,
+    current: Res<CurrentRoom>,
+    assets: Res<AssetServer>,
+    keybindings: Res<Keybindings>,
+    module: Res<Module>,
+    mut er: EventReader<UncbEvent>,
+) {
     for ev in er.read() {
         match &ev.message {
-            UncbMessage::Connected {
-                creds: _,
-                address: _,
-            } => {
-                for stdb_player in StdbPlayer::iter() {
-                    // We cannot spawn the client on startup. We spawn them later with the input manager.
-                    if stdb_player.client_id == spacetimedb_sdk::identity::identity().unwrap() {
-                        continue;
-                    };
-
-                    c.spawn(PlayerBundle::new(Player {
-                        data: stdb_player.clone(),
-                    }));
+            UncbMessage::SubscriptionApplied => {
+                let mut spawned: Vec<u64> = Vec::new();
+                for (entity, player) in q.iter() {
+                    if module
+                        .0
+                        .player(player.data.object_id)
+                        .is_some_and(|stdb_player| stdb_player.room_id == current.room_id)
+                    {
+                        spawned.push(player.data.object_id);
+                    } else {
+                        c.entity(entity).despawn();
+                    }
+                }
+
+                for stdb_player in module.0.players() {
+                    if stdb_player.room_id == current.room_id
+                        && !spawned.contains(&stdb_player.object_id)
+                    {
+                        spawn_player(&mut c, stdb_player, &assets, &module, &keybindings);
+                    }
                 }
             }
             UncbMessage::Disconnected => {
@@ -120,9 +214,16 @@ fn init_players(mut c: Commands, mut er: EventReader<UncbEvent>) {
 }
 
 /// Listens for the `UncbMessage::PlayerInserted` message and spawns all players recently inserted
-/// using a `PlayerBundle`. If the player received in the message has the same `client_id` as the
-/// current client, it adds an input manager onto the player, since it's the client.
-fn refresh_players(mut c: Commands, mut er: EventReader<UncbEvent>) {
+/// into the current room.
+fn refresh_players(
+    mut c: Commands,
+    q: Query<&Player>,
+    current: Res<CurrentRoom>,
+    assets: Res<AssetServer>,
+    keybindings: Res<Keybindings>,
+    module: Res<Module>,
+    mut er: EventReader<UncbEvent>,
+) {
     let mut spawnable_players: Vec<StdbPlayer> = Vec::new();
 
     for ev in er.read() {
@@ -135,25 +236,449 @@ fn refresh_players(mut c: Commands, mut er: EventReader<UncbEvent>) {
     }
 
     for spawn in spawnable_players {
-        info!("Spawned player: {}", identity_leading_hex(&spawn.client_id));
-        let bundle = PlayerBundle::new(Player {
-            data: spawn.clone(),
+        // `init_players` may have already found them in the client cache.
+        if spawn.room_id != current.room_id
+            || q.iter()
+                .any(|player| player.data.object_id == spawn.object_id)
+        {
+            continue;
+        }
+        spawn_player(&mut c, spawn, &assets, &module, &keybindings);
+    }
+}
+
+/// Listens for the `UncbMessage::PlayerUpdated` message and spawns or despawns other players
+/// as they enter or leave the current room. Our own room changes are handled by `init_players`.
+fn move_players(
+    mut c: Commands,
+    q: Query<(Entity, &Player)>,
+    current: Res<CurrentRoom>,
+    assets: Res<AssetServer>,
+    keybindings: Res<Keybindings>,
+    module: Res<Module>,
+    mut er: EventReader<UncbEvent>,
+) {
+    for ev in er.read() {
+        match &ev.message {
+            UncbMessage::PlayerUpdated { old, new, event: _ } => {
+                if old.room_id == new.room_id || module.0.is_local(&new.client_id) {
+                    continue;
+                }
+
+                let spawned = q
+                    .iter()
+                    .find(|(_, player)| player.data.object_id == new.object_id);
+                match spawned {
+                    Some((entity, _)) if new.room_id != current.room_id => {
+                        c.entity(entity).despawn();
+                    }
+                    None if new.room_id == current.room_id => {
+                        spawn_player(&mut c, new.clone(), &assets, &module, &keybindings);
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Spawns a `PlayerBundle` for `stdb_player`. If the player has the same `client_id` as the
+/// current client, it adds an input manager onto the player, since it's the client.
+fn spawn_player(
+    c: &mut Commands,
+    stdb_player: StdbPlayer,
+    assets: &AssetServer,
+    module: &Module,
+    keybindings: &Keybindings,
+) {
+    info!(
+        "Spawned player: {}",
+        identity_leading_hex(&stdb_player.client_id)
+    );
+    let is_local = module.0.is_local(&stdb_player.client_id);
+    let bundle = PlayerBundle::new(Player { data: stdb_player }, assets);
+
+    if is_local {
+        c.spawn(bundle).insert(InputManagerBundle::<GameActions> {
+            // Stores "which actions are currently pressed"
+            action_state: ActionState::default(),
+            // Describes how to convert from player inputs into those actions
+            input_map: input_map(keybindings),
+        });
+    } else {
+        c.spawn(bundle);
+    }
+}
+
+/// Gives the local player the first connected gamepad, and moves them onto another one
+/// when theirs is unplugged, so controllers can come and go while playing.
+fn assign_gamepad(
+    mut q: Query<&mut InputMap<GameActions>>,
+    gamepads: Res<Gamepads>,
+    mut er: EventReader<GamepadConnectionEvent>,
+) {
+    for ev in er.read() {
+        match &ev.connection {
+            GamepadConnection::Connected(info) => info!("Gamepad connected: {}", info.name),
+            GamepadConnection::Disconnected => info!("Gamepad disconnected: {}", ev.gamepad.id),
+        }
+    }
+
+    for mut input_map in &mut q {
+        let current = input_map.gamepad();
+        if current.is_some_and(|gamepad| gamepads.contains(gamepad)) {
+            continue;
+        }
+        match gamepads.iter().next() {
+            Some(gamepad) => {
+                input_map.set_gamepad(gamepad);
+            }
+            None if current.is_some() => {
+                input_map.clear_gamepad();
+            }
+            None => {}
+        }
+    }
+}
+
+/// Listens for the `UncbMessage::PlayerUpdated` message and applies the player's new
+/// color, skin and team to their sprite.
+fn update_appearances(
+    mut q: Query<(&mut Player, &mut Sprite, &mut Handle<Image>)>,
+    assets: Res<AssetServer>,
+    mut er: EventReader<UncbEvent>,
+) {
+    for ev in er.read() {
+        match &ev.message {
+            UncbMessage::PlayerUpdated {
+                old: _,
+                new,
+                event: _,
+            } => {
+                for (mut player, mut sprite, mut texture) in q.iter_mut() {
+                    if player.data.object_id == new.object_id {
+                        sprite.color = player_color(&new.appearance.color, new.team_id);
+                        *texture = skin_texture(&new.appearance.skin, &assets);
+                        player.data = new.clone();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Cycles the local player's color and skin through the allowed options when they press
+/// `GameActions::CycleColor` or `GameActions::CycleSkin`. The sprite only changes once
+/// the server accepts the new appearance.
+fn change_appearance(q: Query<(&ActionState<GameActions>, &Player)>, module: Res<Module>) {
+    for (action_state, player) in &q {
+        let appearance = &player.data.appearance;
+        let mut color = appearance.color.clone();
+        let mut skin = appearance.skin.clone();
+
+        if action_state.just_pressed(GameActions::CycleColor) {
+            color = next_allowed(&color, &ALLOWED_COLORS);
+        }
+        if action_state.just_pressed(GameActions::CycleSkin) {
+            skin = next_allowed(&skin, &ALLOWED_SKINS);
+        }
+
+        if color != appearance.color || skin != appearance.skin {
+            module.0.set_appearance(color, skin);
+        }
+    }
+}
+
+/// Asks the server to walk the local player to the spot right clicked in the world, around
+/// anything solid.
+fn click_to_move(
+    mut c: Commands,
+    q: Query<(Entity, &ActionState<GameActions>), With<Player>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    module: Res<Module>,
+) {
+    for (entity, action_state) in &q {
+        if !action_state.just_pressed(GameActions::MoveTo) {
+            continue;
+        }
+        let Some(destination) = q_window
+            .get_single()
+            .ok()
+            .and_then(Window::cursor_position)
+            .and_then(|cursor| {
+                let (camera, transform) = q_camera.get_single().ok()?;
+                camera.viewport_to_world_2d(transform, cursor)
+            })
+        else {
+            continue;
+        };
+
+        module.0.move_to(StdbVector2 {
+            x: destination.x,
+            y: destination.y,
         });
+        c.entity(entity).insert(Navigating::default());
+    }
+}
+
+/// Listens for `UncbMessage::ObjectUpdated` messages moving the local player that weren't
+/// caused by our own `update_player_pos` calls, like an admin teleport, and snaps the local
+/// player to the new position.
+fn snap_local_player(
+    mut q: Query<(&Player, &mut Transform), With<ActionState<GameActions>>>,
+    mut er: EventReader<UncbEvent>,
+) {
+    for ev in er.read() {
+        match &ev.message {
+            UncbMessage::ObjectUpdated { old: _, new, event } => {
+                if let ReducerEvent::UpdatePlayerPos(_) = event {
+                    continue;
+                }
+
+                for (player, mut transform) in q.iter_mut() {
+                    if player.data.object_id == new.object_id {
+                        transform.translation.x = new.position.x;
+                        transform.translation.y = new.position.y;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bevy::{app::Update, asset::AssetPlugin, input::InputPlugin, MinimalPlugins};
+    use spacetimedb_sdk::identity::Identity;
+
+    use super::*;
+    use crate::{
+        fake_module::{FakeCall, FakeModule},
+        uncb_receiver::{self, process_messages},
+        CreatePlayerArgs, StdbAppearance, StdbObject,
+    };
+
+    const OTHER_ROOM_ID: u64 = 5;
+
+    fn test_app() -> (App, Arc<FakeModule>) {
+        let (uncb_send, uncb_receiver) = uncb_receiver::channel();
+        let module = Arc::new(FakeModule::new(uncb_send));
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            InputPlugin,
+            PlayerPlugin,
+        ))
+        .insert_resource(Module(module.clone()))
+        .insert_resource(uncb_receiver)
+        .init_resource::<CurrentRoom>()
+        .init_resource::<Keybindings>()
+        .add_event::<UncbEvent>()
+        .add_systems(Update, process_messages);
+        (app, module)
+    }
+
+    /// Messages become events one frame and entities the next.
+    fn settle(app: &mut App) {
+        for _ in 0..3 {
+            app.update();
+        }
+    }
+
+    fn identity(n: u8) -> Identity {
+        Identity::from_byte_array([n; 32])
+    }
 
-        if spawn.client_id == spacetimedb_sdk::identity::identity().unwrap() {
-            c.spawn(bundle).insert(InputManagerBundle::<GameActions> {
-                // Stores "which actions are currently pressed"
-                action_state: ActionState::default(),
-                // Describes how to convert from player inputs into those actions
-                input_map: InputMap::new([
-                    (KeyCode::W, GameActions::W),
-                    (KeyCode::A, GameActions::A),
-                    (KeyCode::S, GameActions::S),
-                    (KeyCode::D, GameActions::D),
-                ]),
-            });
-        } else {
-            c.spawn(bundle);
+    fn stdb_player(object_id: u64, client: u8, room_id: u64) -> StdbPlayer {
+        StdbPlayer {
+            object_id,
+            client_id: identity(client),
+            appearance: StdbAppearance {
+                color: "white".to_string(),
+                skin: "default".to_string(),
+            },
+            room_id,
+            team_id: 0,
         }
     }
+
+    fn event() -> ReducerEvent {
+        ReducerEvent::CreatePlayer(CreatePlayerArgs {})
+    }
+
+    fn spawned(app: &mut App) -> Vec<u64> {
+        let mut object_ids: Vec<u64> = app
+            .world
+            .query::<&Player>()
+            .iter(&app.world)
+            .map(|player| player.data.object_id)
+            .collect();
+        object_ids.sort_unstable();
+        object_ids
+    }
+
+    #[test]
+    fn creates_player_on_startup() {
+        let (mut app, module) = test_app();
+        app.update();
+
+        assert_eq!(module.calls().first(), Some(&FakeCall::CreatePlayer));
+    }
+
+    #[test]
+    fn spawns_inserted_players_in_current_room() {
+        let (mut app, module) = test_app();
+        module.insert_player(stdb_player(1, 1, 0), event());
+        module.insert_player(stdb_player(2, 2, OTHER_ROOM_ID), event());
+        settle(&mut app);
+
+        assert_eq!(spawned(&mut app), vec![1]);
+    }
+
+    #[test]
+    fn subscription_spawns_cached_players_once() {
+        let (mut app, module) = test_app();
+        module.load_player(stdb_player(1, 1, 0));
+        module.load_player(stdb_player(2, 2, OTHER_ROOM_ID));
+        module.subscription_applied();
+        settle(&mut app);
+        module.insert_player(stdb_player(1, 1, 0), event());
+        settle(&mut app);
+
+        assert_eq!(spawned(&mut app), vec![1]);
+    }
+
+    #[test]
+    fn subscription_despawns_players_no_longer_cached_in_room() {
+        let (mut app, module) = test_app();
+        module.insert_player(stdb_player(1, 1, 0), event());
+        settle(&mut app);
+        module.load_player(stdb_player(1, 1, OTHER_ROOM_ID));
+        module.load_player(stdb_player(2, 2, 0));
+        module.subscription_applied();
+        settle(&mut app);
+
+        assert_eq!(spawned(&mut app), vec![2]);
+    }
+
+    #[test]
+    fn despawns_removed_players() {
+        let (mut app, module) = test_app();
+        module.insert_player(stdb_player(1, 1, 0), event());
+        module.insert_player(stdb_player(2, 2, 0), event());
+        settle(&mut app);
+        module.delete_player(1, Some(event()));
+        settle(&mut app);
+
+        assert_eq!(spawned(&mut app), vec![2]);
+    }
+
+    #[test]
+    fn despawns_other_players_leaving_the_room() {
+        let (mut app, module) = test_app();
+        module.insert_player(stdb_player(1, 1, 0), event());
+        settle(&mut app);
+        module.update_player(stdb_player(1, 1, OTHER_ROOM_ID), event());
+        settle(&mut app);
+
+        assert!(spawned(&mut app).is_empty());
+    }
+
+    #[test]
+    fn only_local_player_gets_input() {
+        let (mut app, module) = test_app();
+        module.connect(identity(1));
+        module.insert_player(stdb_player(1, 1, 0), event());
+        module.insert_player(stdb_player(2, 2, 0), event());
+        settle(&mut app);
+
+        let local: Vec<u64> = app
+            .world
+            .query_filtered::<&Player, With<ActionState<GameActions>>>()
+            .iter(&app.world)
+            .map(|player| player.data.object_id)
+            .collect();
+        assert_eq!(local, vec![1]);
+        assert!(module
+            .calls()
+            .iter()
+            .any(|call| matches!(call, FakeCall::UpdatePlayerPos(_))));
+    }
+
+    #[test]
+    fn navigating_player_follows_the_server_until_the_path_is_done() {
+        let (mut app, module) = test_app();
+        module.connect(identity(1));
+        module.insert_player(stdb_player(1, 1, 0), event());
+        settle(&mut app);
+
+        module.load_object(StdbObject {
+            object_id: 1,
+            name: String::new(),
+            kind: "player".to_string(),
+            room_id: 0,
+            position: StdbVector2 { x: 40.0, y: 30.0 },
+            velocity: StdbVector2 { x: 0.0, y: 0.0 },
+            facing: 0.0,
+            moved_at_micros: 0,
+        });
+        module.load_path(1, true);
+        let local = app
+            .world
+            .query_filtered::<Entity, With<ActionState<GameActions>>>()
+            .single(&app.world);
+        app.world.entity_mut(local).insert(Navigating::default());
+        let sent = module.calls().len();
+        app.update();
+
+        let translation = app.world.get::<Transform>(local).unwrap().translation;
+        assert_eq!((translation.x, translation.y), (40.0, 30.0));
+        assert_eq!(module.calls().len(), sent);
+
+        module.load_path(1, false);
+        app.update();
+        assert!(app.world.get::<Navigating>(local).is_none());
+        assert!(matches!(
+            module.calls().last(),
+            Some(FakeCall::UpdatePlayerPos(_))
+        ));
+    }
+
+    #[test]
+    fn navigating_player_waits_for_a_path_it_hasnt_seen_yet() {
+        let (mut app, module) = test_app();
+        module.connect(identity(1));
+        module.insert_player(stdb_player(1, 1, 0), event());
+        settle(&mut app);
+
+        let local = app
+            .world
+            .query_filtered::<Entity, With<ActionState<GameActions>>>()
+            .single(&app.world);
+        app.world.entity_mut(local).insert(Navigating::default());
+        let sent = module.calls().len();
+        app.update();
+        app.update();
+
+        assert!(app.world.get::<Navigating>(local).is_some());
+        assert_eq!(module.calls().len(), sent);
+
+        module.load_path(1, true);
+        app.update();
+        module.load_path(1, false);
+        app.update();
+        assert!(app.world.get::<Navigating>(local).is_none());
+        assert!(matches!(
+            module.calls().last(),
+            Some(FakeCall::UpdatePlayerPos(_))
+        ));
+    }
 }
@@ -0,0 +1,50 @@
+use bevy::{
+    ecs::component::Component,
+    time::{Timer, TimerMode},
+};
+
+/// How long the screen takes to fade back in after the local player steps through a portal.
+pub const PORTAL_FADE_SECS: f32 = 0.4;
+
+/// Covers the screen while the local player jumps through a portal, hiding the camera
+/// snapping to where they landed.
+#[derive(Component)]
+pub struct PortalFade {
+    pub timer: Timer,
+}
+
+impl Default for PortalFade {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(PORTAL_FADE_SECS, TimerMode::Once);
+        // Nothing to fade in from until the first jump.
+        timer.tick(timer.duration());
+        Self { timer }
+    }
+}
+
+impl PortalFade {
+    /// How opaque the cover is, fully right after the jump and clear once the timer finishes.
+    pub fn alpha(&self) -> f32 {
+        1.0 - self.timer.percent()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn fades_only_after_a_jump() {
+        let mut fade = PortalFade::default();
+        assert_eq!(fade.alpha(), 0.0);
+
+        fade.timer.reset();
+        assert_eq!(fade.alpha(), 1.0);
+
+        fade.timer
+            .tick(Duration::from_secs_f32(PORTAL_FADE_SECS / 2.0));
+        assert!((fade.alpha() - 0.5).abs() < 0.01);
+    }
+}
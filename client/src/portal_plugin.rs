@@ -0,0 +1,87 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    ecs::{
+        event::EventReader,
+        query::{With, Without},
+        system::{Commands, Query, Res},
+    },
+    render::color::Color,
+    time::Time,
+    transform::components::Transform,
+    ui::{node_bundles::NodeBundle, BackgroundColor, PositionType, Style, Val, ZIndex},
+};
+use leafwing_input_manager::action_state::ActionState;
+
+use crate::{
+    actions::GameActions,
+    camera::MainCamera,
+    module::Module,
+    player::Player,
+    portal::PortalFade,
+    uncb_receiver::{UncbEvent, UncbMessage},
+};
+
+pub struct PortalPlugin;
+impl Plugin for PortalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (init_portal_fade,))
+            .add_systems(Update, (jump_local_player, fade_in));
+    }
+}
+
+/// Spawns the cover drawn over everything else while the screen fades back in after a jump.
+fn init_portal_fade(mut c: Commands) {
+    c.spawn((
+        PortalFade::default(),
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..Default::default()
+            },
+            background_color: Color::NONE.into(),
+            z_index: ZIndex::Global(i32::MAX),
+            ..Default::default()
+        },
+    ));
+}
+
+/// Listens for the `UncbMessage::PortalJumped` message for the local player, snaps them and
+/// the camera to where they landed instead of easing across the world, and starts the fade.
+/// Other players are placed by the server with no velocity, so they don't slide either.
+fn jump_local_player(
+    mut players: Query<&mut Transform, (With<Player>, With<ActionState<GameActions>>)>,
+    mut cameras: Query<&mut Transform, (With<MainCamera>, Without<Player>)>,
+    mut fades: Query<&mut PortalFade>,
+    module: Res<Module>,
+    mut er: EventReader<UncbEvent>,
+) {
+    for ev in er.read() {
+        let UncbMessage::PortalJumped { data, event: _ } = &ev.message else {
+            continue;
+        };
+        if !module.0.is_local(&data.client_id) {
+            continue;
+        }
+
+        for mut transform in &mut players {
+            transform.translation.x = data.to.x;
+            transform.translation.y = data.to.y;
+        }
+        for mut transform in &mut cameras {
+            transform.translation.x = data.to.x;
+            transform.translation.y = data.to.y;
+        }
+        for mut fade in &mut fades {
+            fade.timer.reset();
+        }
+    }
+}
+
+fn fade_in(time: Res<Time>, mut q: Query<(&mut PortalFade, &mut BackgroundColor)>) {
+    for (mut fade, mut background) in &mut q {
+        fade.timer.tick(time.delta());
+        background.0 = Color::rgba(0.0, 0.0, 0.0, fade.alpha());
+    }
+}
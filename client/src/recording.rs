@@ -0,0 +1,458 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    time::Duration,
+};
+
+use bevy::ecs::{component::Component, system::Resource};
+use spacetimedb_sdk::{
+    sats::{de::Deserialize, ser::Serialize},
+    spacetimedb_lib::bsatn,
+};
+
+use crate::{
+    uncb_receiver::{UncbMessage, UncbSend},
+    ReducerEvent, StdbChatMessage, StdbInventoryItem, StdbObject, StdbPlayer, StdbPortalJump,
+    StdbZoneEvent,
+};
+
+/// Fastest and slowest a replay can be played back at.
+pub const REPLAY_SPEED_RANGE: (f32, f32) = (0.25, 8.0);
+/// How far the arrow keys skip through a replay.
+pub const REPLAY_SKIP: Duration = Duration::from_secs(10);
+
+#[derive(Component)]
+pub struct ReplayLabel;
+
+/// A single message received while recording, `at` is the time since the recording started.
+pub struct RecordedEntry {
+    pub at: Duration,
+    pub message: RecordedMessage,
+}
+
+pub enum RecordedMessage {
+    Message(UncbMessage),
+    /// Rows from a subscription never send insert messages, so `UncbMessage::SubscriptionApplied`
+    /// is recorded along with the players and objects in the client cache at the time.
+    SubscriptionApplied {
+        players: Vec<StdbPlayer>,
+        objects: Vec<StdbObject>,
+    },
+}
+
+/// Appends every `UncbMessage` to a file while the client is started with `--record <path>`.
+#[derive(Resource)]
+pub struct Recorder {
+    pub file: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            file: BufWriter::new(File::create(path)?),
+        })
+    }
+
+    /// Writes one entry as its length followed by its bytes, see `encode_entry`.
+    pub fn write(&mut self, entry: &RecordedEntry) -> io::Result<()> {
+        let bytes = encode_entry(entry);
+        self.file.write_all(&(bytes.len() as u32).to_le_bytes())?;
+        self.file.write_all(&bytes)
+    }
+}
+
+/// Plays back a recording while the client is started with `--replay <path>`. Messages are
+/// sent into the same channel as the SDK callbacks, so they reach the plugins as `UncbEvent`s.
+#[derive(Resource)]
+pub struct Replay {
+    pub entries: Vec<RecordedEntry>,
+    /// Index of the next entry to play.
+    pub cursor: usize,
+    /// How far into the recording playback is.
+    pub clock: Duration,
+    pub speed: f32,
+    pub paused: bool,
+    /// Frames since a message was last sent, see `ReplayPlugin` for why this is needed.
+    pub quiet_frames: u32,
+    pub send: UncbSend,
+}
+
+impl Replay {
+    pub fn load(path: &Path, send: UncbSend) -> io::Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+        let mut entries = Vec::new();
+
+        let mut len = [0; 4];
+        loop {
+            match file.read_exact(&mut len) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            }
+            let mut bytes = vec![0; u32::from_le_bytes(len) as usize];
+            file.read_exact(&mut bytes)?;
+
+            // Entries whose reducer event can't be rebuilt are skipped rather than failing the
+            // whole replay, e.g. a recording from before a reducer was renamed.
+            match decode_entry(&bytes)? {
+                Some(entry) => entries.push(entry),
+                None => eprintln!("Skipped an entry that can't be replayed"),
+            }
+        }
+
+        Ok(Self {
+            entries,
+            cursor: 0,
+            clock: Duration::ZERO,
+            speed: 1.0,
+            paused: false,
+            quiet_frames: 0,
+            send,
+        })
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.entries.last().map_or(Duration::ZERO, |entry| entry.at)
+    }
+}
+
+// Entries are the time in micros, a tag for the kind of message, then every row and reducer
+// event of the message as a length prefixed BSATN blob.
+const TAG_CONNECTED: u8 = 0;
+const TAG_DISCONNECTED: u8 = 1;
+const TAG_SUBSCRIPTION_APPLIED: u8 = 2;
+const TAG_PLAYER_INSERTED: u8 = 3;
+const TAG_PLAYER_UPDATED: u8 = 4;
+const TAG_PLAYER_REMOVED: u8 = 5;
+const TAG_OBJECT_INSERTED: u8 = 6;
+const TAG_OBJECT_UPDATED: u8 = 7;
+const TAG_OBJECT_REMOVED: u8 = 8;
+const TAG_INVENTORY_ITEM_INSERTED: u8 = 9;
+const TAG_INVENTORY_ITEM_UPDATED: u8 = 10;
+const TAG_INVENTORY_ITEM_REMOVED: u8 = 11;
+const TAG_CHAT_MESSAGE_INSERTED: u8 = 12;
+const TAG_ZONE_EVENT_INSERTED: u8 = 13;
+const TAG_PORTAL_JUMPED: u8 = 14;
+
+fn encode_entry(entry: &RecordedEntry) -> Vec<u8> {
+    let mut w = EntryWriter {
+        bytes: (entry.at.as_micros() as u64).to_le_bytes().to_vec(),
+    };
+
+    match &entry.message {
+        // The credentials hold this client's private token, so they're never written out.
+        RecordedMessage::Message(UncbMessage::Connected { .. }) => w.tag(TAG_CONNECTED),
+        RecordedMessage::Message(UncbMessage::Disconnected) => w.tag(TAG_DISCONNECTED),
+        RecordedMessage::Message(UncbMessage::SubscriptionApplied) => {
+            w.tag(TAG_SUBSCRIPTION_APPLIED);
+            w.rows(&[] as &[StdbPlayer]);
+            w.rows(&[] as &[StdbObject]);
+        }
+        RecordedMessage::SubscriptionApplied { players, objects } => {
+            w.tag(TAG_SUBSCRIPTION_APPLIED);
+            w.rows(players);
+            w.rows(objects);
+        }
+        RecordedMessage::Message(UncbMessage::PlayerInserted { data, event }) => {
+            w.tag(TAG_PLAYER_INSERTED);
+            w.row(data);
+            w.event(Some(event));
+        }
+        RecordedMessage::Message(UncbMessage::PlayerUpdated { old, new, event }) => {
+            w.tag(TAG_PLAYER_UPDATED);
+            w.row(old);
+            w.row(new);
+            w.event(Some(event));
+        }
+        RecordedMessage::Message(UncbMessage::PlayerRemoved { data, event }) => {
+            w.tag(TAG_PLAYER_REMOVED);
+            w.row(data);
+            w.event(event.as_ref());
+        }
+        RecordedMessage::Message(UncbMessage::ObjectInserted { data, event }) => {
+            w.tag(TAG_OBJECT_INSERTED);
+            w.row(data);
+            w.event(Some(event));
+        }
+        RecordedMessage::Message(UncbMessage::ObjectUpdated { old, new, event }) => {
+            w.tag(TAG_OBJECT_UPDATED);
+            w.row(old);
+            w.row(new);
+            w.event(Some(event));
+        }
+        RecordedMessage::Message(UncbMessage::ObjectRemoved { data, event }) => {
+            w.tag(TAG_OBJECT_REMOVED);
+            w.row(data);
+            w.event(Some(event));
+        }
+        RecordedMessage::Message(UncbMessage::InventoryItemInserted { data, event }) => {
+            w.tag(TAG_INVENTORY_ITEM_INSERTED);
+            w.row(data);
+            w.event(Some(event));
+        }
+        RecordedMessage::Message(UncbMessage::InventoryItemUpdated { old, new, event }) => {
+            w.tag(TAG_INVENTORY_ITEM_UPDATED);
+            w.row(old);
+            w.row(new);
+            w.event(Some(event));
+        }
+        RecordedMessage::Message(UncbMessage::InventoryItemRemoved { data, event }) => {
+            w.tag(TAG_INVENTORY_ITEM_REMOVED);
+            w.row(data);
+            w.event(Some(event));
+        }
+        RecordedMessage::Message(UncbMessage::ChatMessageInserted { data, event }) => {
+            w.tag(TAG_CHAT_MESSAGE_INSERTED);
+            w.row(data);
+            w.event(Some(event));
+        }
+        RecordedMessage::Message(UncbMessage::ZoneEventInserted { data, event }) => {
+            w.tag(TAG_ZONE_EVENT_INSERTED);
+            w.row(data);
+            w.event(Some(event));
+        }
+        RecordedMessage::Message(UncbMessage::PortalJumped { data, event }) => {
+            w.tag(TAG_PORTAL_JUMPED);
+            w.row(data);
+            w.event(Some(event));
+        }
+    }
+
+    w.bytes
+}
+
+/// Returns `None` for entries whose reducer event isn't known to this client.
+fn decode_entry(bytes: &[u8]) -> io::Result<Option<RecordedEntry>> {
+    let mut r = EntryReader { bytes };
+    let at = Duration::from_micros(u64::from_le_bytes(r.take(8)?.try_into().unwrap()));
+
+    let message = match r.take(1)?[0] {
+        TAG_CONNECTED => {
+            // There's nothing to connect to, nothing listens for this while replaying.
+            return Ok(None);
+        }
+        TAG_DISCONNECTED => RecordedMessage::Message(UncbMessage::Disconnected),
+        TAG_SUBSCRIPTION_APPLIED => RecordedMessage::SubscriptionApplied {
+            players: r.rows()?,
+            objects: r.rows()?,
+        },
+        TAG_PLAYER_INSERTED => {
+            let data = r.row()?;
+            let Some(event) = r.event()? else {
+                return Ok(None);
+            };
+            RecordedMessage::Message(UncbMessage::PlayerInserted { data, event })
+        }
+        TAG_PLAYER_UPDATED => {
+            let (old, new) = (r.row()?, r.row()?);
+            let Some(event) = r.event()? else {
+                return Ok(None);
+            };
+            RecordedMessage::Message(UncbMessage::PlayerUpdated { old, new, event })
+        }
+        TAG_PLAYER_REMOVED => RecordedMessage::Message(UncbMessage::PlayerRemoved {
+            data: r.row()?,
+            event: r.event()?,
+        }),
+        TAG_OBJECT_INSERTED => {
+            let data = r.row()?;
+            let Some(event) = r.event()? else {
+                return Ok(None);
+            };
+            RecordedMessage::Message(UncbMessage::ObjectInserted { data, event })
+        }
+        TAG_OBJECT_UPDATED => {
+            let (old, new) = (r.row()?, r.row()?);
+            let Some(event) = r.event()? else {
+                return Ok(None);
+            };
+            RecordedMessage::Message(UncbMessage::ObjectUpdated { old, new, event })
+        }
+        TAG_OBJECT_REMOVED => {
+            let data = r.row()?;
+            let Some(event) = r.event()? else {
+                return Ok(None);
+            };
+            RecordedMessage::Message(UncbMessage::ObjectRemoved { data, event })
+        }
+        TAG_INVENTORY_ITEM_INSERTED => {
+            let data: StdbInventoryItem = r.row()?;
+            let Some(event) = r.event()? else {
+                return Ok(None);
+            };
+            RecordedMessage::Message(UncbMessage::InventoryItemInserted { data, event })
+        }
+        TAG_INVENTORY_ITEM_UPDATED => {
+            let (old, new) = (r.row()?, r.row()?);
+            let Some(event) = r.event()? else {
+                return Ok(None);
+            };
+            RecordedMessage::Message(UncbMessage::InventoryItemUpdated { old, new, event })
+        }
+        TAG_INVENTORY_ITEM_REMOVED => {
+            let data = r.row()?;
+            let Some(event) = r.event()? else {
+                return Ok(None);
+            };
+            RecordedMessage::Message(UncbMessage::InventoryItemRemoved { data, event })
+        }
+        TAG_CHAT_MESSAGE_INSERTED => {
+            let data: StdbChatMessage = r.row()?;
+            let Some(event) = r.event()? else {
+                return Ok(None);
+            };
+            RecordedMessage::Message(UncbMessage::ChatMessageInserted { data, event })
+        }
+        TAG_ZONE_EVENT_INSERTED => {
+            let data: StdbZoneEvent = r.row()?;
+            let Some(event) = r.event()? else {
+                return Ok(None);
+            };
+            RecordedMessage::Message(UncbMessage::ZoneEventInserted { data, event })
+        }
+        TAG_PORTAL_JUMPED => {
+            let data: StdbPortalJump = r.row()?;
+            let Some(event) = r.event()? else {
+                return Ok(None);
+            };
+            RecordedMessage::Message(UncbMessage::PortalJumped { data, event })
+        }
+        tag => return Err(invalid_data(format!("Unknown message tag {}", tag))),
+    };
+
+    Ok(Some(RecordedEntry { at, message }))
+}
+
+struct EntryWriter {
+    bytes: Vec<u8>,
+}
+
+impl EntryWriter {
+    fn tag(&mut self, tag: u8) {
+        self.bytes.push(tag);
+    }
+
+    fn blob(&mut self, blob: &[u8]) {
+        self.bytes
+            .extend_from_slice(&(blob.len() as u32).to_le_bytes());
+        self.bytes.extend_from_slice(blob);
+    }
+
+    fn row<T: Serialize>(&mut self, row: &T) {
+        self.blob(&bsatn::to_vec(row).expect("Failed to encode a row"));
+    }
+
+    fn rows<T: Serialize>(&mut self, rows: &[T]) {
+        self.bytes
+            .extend_from_slice(&(rows.len() as u32).to_le_bytes());
+        for row in rows {
+            self.row(row);
+        }
+    }
+
+    /// An empty name means there was no reducer event.
+    fn event(&mut self, event: Option<&ReducerEvent>) {
+        match event.and_then(encode_reducer_event) {
+            Some((reducer, args)) => {
+                self.blob(reducer.as_bytes());
+                self.blob(&args);
+            }
+            None => {
+                self.blob(&[]);
+                self.blob(&[]);
+            }
+        }
+    }
+}
+
+struct EntryReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> EntryReader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(invalid_data("Entry ended early".to_string()));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn len(&mut self) -> io::Result<usize> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize)
+    }
+
+    fn blob(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.len()?;
+        self.take(len)
+    }
+
+    fn row<T: for<'de> Deserialize<'de>>(&mut self) -> io::Result<T> {
+        bsatn::from_slice(self.blob()?).map_err(|e| invalid_data(format!("{:?}", e)))
+    }
+
+    fn rows<T: for<'de> Deserialize<'de>>(&mut self) -> io::Result<Vec<T>> {
+        let len = self.len()?;
+        (0..len).map(|_| self.row()).collect()
+    }
+
+    /// `Ok(None)` when there was no reducer event, or it's one this client doesn't know.
+    fn event(&mut self) -> io::Result<Option<ReducerEvent>> {
+        let reducer = String::from_utf8_lossy(self.blob()?).into_owned();
+        let args = self.blob()?;
+        Ok(decode_reducer_event(&reducer, args))
+    }
+}
+
+/// Every reducer whose events can be recorded, each one is stored by its variant name with
+/// its arguments encoded as BSATN.
+macro_rules! recorded_reducers {
+    ($($reducer:ident),* $(,)?) => {
+        fn encode_reducer_event(event: &ReducerEvent) -> Option<(&'static str, Vec<u8>)> {
+            match event {
+                $(ReducerEvent::$reducer(args) => {
+                    Some((stringify!($reducer), bsatn::to_vec(args).ok()?))
+                })*
+                #[allow(unreachable_patterns)]
+                _ => None,
+            }
+        }
+
+        fn decode_reducer_event(reducer: &str, args: &[u8]) -> Option<ReducerEvent> {
+            match reducer {
+                $(stringify!($reducer) => bsatn::from_slice(args).ok().map(ReducerEvent::$reducer),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+recorded_reducers!(
+    BanPlayer,
+    ConfigureMatch,
+    CreatePlayer,
+    CreateRoom,
+    JoinRoom,
+    KickPlayer,
+    LeaveRoom,
+    MoveTo,
+    MutePlayer,
+    PickupItem,
+    Ping,
+    PlaceProp,
+    RemoveProp,
+    SendChatMessage,
+    SendTeamMessage,
+    SetAppearance,
+    SetDayLength,
+    SwitchTeam,
+    TeleportPlayer,
+    Tick,
+    UpdatePlayerPos,
+);
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
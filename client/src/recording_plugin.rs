@@ -0,0 +1,61 @@
+use std::io::Write;
+
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        event::EventReader,
+        system::{Res, ResMut},
+    },
+    time::Time,
+};
+use spacetimedb_sdk::table::TableType;
+
+use crate::{
+    recording::{RecordedEntry, RecordedMessage, Recorder},
+    uncb_receiver::{UncbEvent, UncbMessage},
+    StdbObject, StdbPlayer,
+};
+
+/// Writes every `UncbMessage` the client receives to the `Recorder`, only added when the
+/// client is started with `--record <path>`.
+pub struct RecordingPlugin;
+impl Plugin for RecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (record_messages,));
+    }
+}
+
+/// Writes the `UncbEvent`s of this frame with the time they arrived. The file is flushed every
+/// frame since `UncbMessage::Disconnected` exits the client before it's ever read.
+fn record_messages(
+    time: Res<Time>,
+    mut recorder: ResMut<Recorder>,
+    mut er: EventReader<UncbEvent>,
+) {
+    let mut wrote = false;
+
+    for ev in er.read() {
+        let message = match &ev.message {
+            UncbMessage::SubscriptionApplied => RecordedMessage::SubscriptionApplied {
+                players: StdbPlayer::iter().collect(),
+                objects: StdbObject::iter().collect(),
+            },
+            message => RecordedMessage::Message(message.clone()),
+        };
+
+        let entry = RecordedEntry {
+            at: time.elapsed(),
+            message,
+        };
+        if let Err(e) = recorder.write(&entry) {
+            eprintln!("Failed to record a message: {}", e);
+        }
+        wrote = true;
+    }
+
+    if wrote {
+        if let Err(e) = recorder.file.flush() {
+            eprintln!("Failed to flush the recording: {}", e);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    asset::AssetServer,
+    ecs::{
+        entity::Entity,
+        event::EventReader,
+        query::{Or, With},
+        schedule::IntoSystemConfigs,
+        system::{Commands, Query, Res, ResMut},
+    },
+    input::{keyboard::KeyCode, Input},
+    render::color::Color,
+    text::{Text, TextStyle},
+    time::Time,
+    transform::components::Transform,
+    ui::{node_bundles::TextBundle, PositionType, Style, Val},
+};
+
+use crate::{
+    object::{ObjectBundle, ObjectRegistry, WorldObject, OBJECT_KIND_PLAYER},
+    player::{Player, PlayerBundle},
+    recording::{RecordedMessage, Replay, ReplayLabel, REPLAY_SKIP, REPLAY_SPEED_RANGE},
+    uncb_receiver::{process_messages, UncbEvent, UncbMessage},
+};
+
+/// Plays back the `Replay` instead of connecting to the server, only added when the client
+/// is started with `--replay <path>`.
+///
+/// Recorded subscriptions respawn every player and object directly, but the messages before
+/// them only become entities a couple of frames after they're sent. So a subscription waits
+/// for `Replay::quiet_frames` to pass first, otherwise those entities would outlive it.
+pub struct ReplayPlugin;
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (init_replay_label,)).add_systems(
+            Update,
+            (
+                control_replay,
+                play_replay.before(process_messages),
+                move_replayed_players,
+                refresh_replay_label,
+            ),
+        );
+    }
+}
+
+/// Frames a recorded subscription waits after the last message, see `ReplayPlugin`.
+const SUBSCRIPTION_QUIET_FRAMES: u32 = 2;
+
+/// Spawns the label showing the playback position along the bottom of the screen.
+fn init_replay_label(mut c: Commands) {
+    c.spawn((
+        ReplayLabel,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..Default::default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(300.0),
+            ..Default::default()
+        }),
+    ));
+}
+
+/// Space pauses, Up and Down change the speed, Right skips ahead and Left skips back.
+/// Skipping back replays the recording from the start, up to the new position.
+fn control_replay(
+    mut c: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut replay: ResMut<Replay>,
+    q: Query<Entity, Or<(With<Player>, With<WorldObject>)>>,
+) {
+    let (min_speed, max_speed) = REPLAY_SPEED_RANGE;
+
+    if keys.just_pressed(KeyCode::Space) {
+        replay.paused = !replay.paused;
+    }
+    if keys.just_pressed(KeyCode::Up) {
+        replay.speed = (replay.speed * 2.0).min(max_speed);
+    }
+    if keys.just_pressed(KeyCode::Down) {
+        replay.speed = (replay.speed / 2.0).max(min_speed);
+    }
+    if keys.just_pressed(KeyCode::Right) {
+        replay.clock = (replay.clock + REPLAY_SKIP).min(replay.duration());
+    }
+    if keys.just_pressed(KeyCode::Left) {
+        replay.clock = replay.clock.saturating_sub(REPLAY_SKIP);
+        replay.cursor = 0;
+        replay.quiet_frames = 0;
+        for entity in q.iter() {
+            c.entity(entity).despawn();
+        }
+    }
+}
+
+/// Sends every entry up to the playback position into the `UncbReceiver` channel, and
+/// respawns the players and objects of recorded subscriptions.
+fn play_replay(
+    mut c: Commands,
+    time: Res<Time>,
+    mut replay: ResMut<Replay>,
+    registry: Res<ObjectRegistry>,
+    assets: Res<AssetServer>,
+    q: Query<Entity, Or<(With<Player>, With<WorldObject>)>>,
+) {
+    let replay = &mut *replay;
+    if !replay.paused {
+        replay.clock += time.delta().mul_f32(replay.speed);
+    }
+
+    let mut sent = false;
+    while let Some(entry) = replay.entries.get(replay.cursor) {
+        if entry.at > replay.clock {
+            break;
+        }
+
+        match &entry.message {
+            RecordedMessage::Message(message) => {
+                replay.send.unbounded_send(message.clone()).unwrap();
+                sent = true;
+            }
+            RecordedMessage::SubscriptionApplied { players, objects } => {
+                if sent || replay.quiet_frames < SUBSCRIPTION_QUIET_FRAMES {
+                    break;
+                }
+
+                for entity in q.iter() {
+                    c.entity(entity).despawn();
+                }
+                for object in objects {
+                    if object.kind != OBJECT_KIND_PLAYER {
+                        c.spawn(ObjectBundle::new(
+                            WorldObject {
+                                data: object.clone(),
+                            },
+                            &registry,
+                            &assets,
+                        ));
+                        continue;
+                    }
+
+                    // Players are only in the subscribed room if their object is.
+                    let Some(player) = players.iter().find(|p| p.object_id == object.object_id)
+                    else {
+                        continue;
+                    };
+                    let mut bundle = PlayerBundle::new(
+                        Player {
+                            data: player.clone(),
+                        },
+                        &assets,
+                    );
+                    bundle.sprite_bundle.transform.translation.x = object.position.x;
+                    bundle.sprite_bundle.transform.translation.y = object.position.y;
+                    c.spawn(bundle);
+                }
+            }
+        }
+        replay.cursor += 1;
+    }
+
+    replay.quiet_frames = if sent { 0 } else { replay.quiet_frames + 1 };
+}
+
+/// Listens for the `UncbMessage::ObjectUpdated` message and moves the matching player. Live
+/// players follow the client cache instead, which is empty while replaying.
+fn move_replayed_players(mut q: Query<(&Player, &mut Transform)>, mut er: EventReader<UncbEvent>) {
+    for ev in er.read() {
+        match &ev.message {
+            UncbMessage::ObjectUpdated {
+                old: _,
+                new,
+                event: _,
+            } => {
+                for (player, mut transform) in q.iter_mut() {
+                    if player.data.object_id == new.object_id {
+                        transform.translation.x = new.position.x;
+                        transform.translation.y = new.position.y;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn refresh_replay_label(replay: Res<Replay>, mut q: Query<&mut Text, With<ReplayLabel>>) {
+    let clock = replay.clock.as_secs();
+    let duration = replay.duration().as_secs();
+    let label = format!(
+        "Replay {}:{:02} / {}:{:02}  {}x{}",
+        clock / 60,
+        clock % 60,
+        duration / 60,
+        duration % 60,
+        replay.speed,
+        if replay.paused { "  Paused" } else { "" }
+    );
+
+    for mut text in &mut q {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use bevy::ecs::{component::Component, system::Resource};
+
+/// `room_id` of the shared lobby every player starts in, this should match `LOBBY_ROOM_ID` on the server.
+pub const LOBBY_ROOM_ID: u64 = 0;
+
+/// The room the local player is in, only objects and players in this room are mirrored.
+#[derive(Resource, Default)]
+pub struct CurrentRoom {
+    pub room_id: u64,
+}
+
+#[derive(Component)]
+pub struct RoomLabel;
@@ -0,0 +1,117 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    ecs::{
+        event::EventReader,
+        query::With,
+        system::{Commands, Query, Res, ResMut},
+    },
+    log::info,
+    render::color::Color,
+    text::{Text, TextStyle},
+    ui::{node_bundles::TextBundle, PositionType, Style, Val},
+};
+
+use crate::{
+    is_local,
+    room::{CurrentRoom, RoomLabel, LOBBY_ROOM_ID},
+    subscribe_to,
+    team::{team_name, CurrentTeam, NO_TEAM},
+    uncb_receiver::{UncbEvent, UncbMessage},
+    StdbPlayer, StdbRoom,
+};
+
+pub struct RoomPlugin;
+impl Plugin for RoomPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentRoom>()
+            .init_resource::<CurrentTeam>()
+            .add_systems(Startup, (init_room_label,))
+            .add_systems(Update, (follow_room, refresh_room_label));
+    }
+}
+
+/// Spawns the label showing the current room and team in the top left corner of the screen.
+fn init_room_label(mut c: Commands) {
+    c.spawn((
+        RoomLabel,
+        TextBundle::from_section(
+            room_label_text(LOBBY_ROOM_ID, NO_TEAM),
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..Default::default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..Default::default()
+        }),
+    ));
+}
+
+/// Listens for the `UncbMessage::PlayerInserted` and `UncbMessage::PlayerUpdated` messages for
+/// the local player and, when they changed rooms or teams, replaces the subscription with one
+/// for the new room and team. The players and objects are swapped out once the
+/// `UncbMessage::SubscriptionApplied` message arrives.
+fn follow_room(
+    mut current: ResMut<CurrentRoom>,
+    mut current_team: ResMut<CurrentTeam>,
+    mut er: EventReader<UncbEvent>,
+) {
+    for ev in er.read() {
+        let player: &StdbPlayer = match &ev.message {
+            UncbMessage::PlayerInserted { data, event: _ } => data,
+            UncbMessage::PlayerUpdated {
+                old: _,
+                new,
+                event: _,
+            } => new,
+            _ => continue,
+        };
+        if !is_local(&player.client_id)
+            || (player.room_id == current.room_id && player.team_id == current_team.team_id)
+        {
+            continue;
+        }
+
+        info!(
+            "Moved to room {} on team {}",
+            player.room_id, player.team_id
+        );
+        current.room_id = player.room_id;
+        current_team.team_id = player.team_id;
+        subscribe_to(player.room_id, player.team_id);
+    }
+}
+
+fn refresh_room_label(
+    current: Res<CurrentRoom>,
+    current_team: Res<CurrentTeam>,
+    mut q: Query<&mut Text, With<RoomLabel>>,
+) {
+    if !current.is_changed() && !current_team.is_changed() {
+        return;
+    }
+
+    for mut text in &mut q {
+        text.sections[0].value = room_label_text(current.room_id, current_team.team_id);
+    }
+}
+
+fn room_label_text(room_id: u64, team_id: u64) -> String {
+    let room = if room_id == LOBBY_ROOM_ID {
+        "Lobby".to_string()
+    } else {
+        match StdbRoom::filter_by_room_id(room_id) {
+            Some(room) => format!("Room {}: {}", room.room_id, room.name),
+            None => format!("Room {}", room_id),
+        }
+    };
+
+    match team_id {
+        NO_TEAM => room,
+        _ => format!("{} - {}", room, team_name(team_id)),
+    }
+}
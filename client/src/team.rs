@@ -0,0 +1,36 @@
+use bevy::{ecs::system::Resource, render::color::Color};
+use spacetimedb_sdk::table::TableType;
+
+use crate::{appearance::appearance_color, StdbTeam};
+
+/// `team_id` of chat messages sent to everyone, this should match `NO_TEAM` on the server.
+pub const NO_TEAM: u64 = 0;
+/// How far a player's own color is mixed towards their team's color.
+pub const TEAM_TINT: f32 = 0.5;
+
+/// The team the local player is on, its chat is delivered to us while we're on it.
+#[derive(Resource, Default)]
+pub struct CurrentTeam {
+    pub team_id: u64,
+}
+
+/// A player's appearance color tinted by the color of the team with `team_id`.
+pub fn player_color(color: &str, team_id: u64) -> Color {
+    let color = appearance_color(color);
+    let Some(team) = StdbTeam::filter_by_team_id(team_id) else {
+        return color;
+    };
+
+    let [r, g, b, a] = color.as_rgba_f32();
+    let [team_r, team_g, team_b, _] = appearance_color(&team.color).as_rgba_f32();
+    Color::rgba(
+        r + (team_r - r) * TEAM_TINT,
+        g + (team_g - g) * TEAM_TINT,
+        b + (team_b - b) * TEAM_TINT,
+        a,
+    )
+}
+
+pub fn team_name(team_id: u64) -> String {
+    StdbTeam::filter_by_team_id(team_id).map_or(format!("Team {}", team_id), |team| team.name)
+}
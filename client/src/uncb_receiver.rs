@@ -1,3 +1,8 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
 use bevy::ecs::{
     event::Event,
     system::{Commands, ResMut, Resource},
@@ -6,7 +11,10 @@ use bevy::ecs::{
 use futures_channel::mpsc;
 use spacetimedb_sdk::{identity::Credentials, Address};
 
-use crate::{ReducerEvent, StdbObject, StdbPlayer};
+use crate::{
+    ReducerEvent, StdbChatMessage, StdbInventoryItem, StdbObject, StdbPlayer, StdbPortalJump,
+    StdbZoneEvent,
+};
 
 /// Unbound Callback Message
 /// Used to tell our unbounded reciever what \
@@ -19,6 +27,7 @@ pub enum UncbMessage {
         address: Address,
     },
     Disconnected,
+    SubscriptionApplied,
 
     PlayerInserted {
         data: StdbPlayer,
@@ -31,6 +40,7 @@ pub enum UncbMessage {
     },
     PlayerRemoved {
         data: StdbPlayer,
+        event: Option<ReducerEvent>,
     },
     ObjectInserted {
         data: StdbObject,
@@ -45,22 +55,107 @@ pub enum UncbMessage {
         data: StdbObject,
         event: ReducerEvent,
     },
+    InventoryItemInserted {
+        data: StdbInventoryItem,
+        event: ReducerEvent,
+    },
+    InventoryItemUpdated {
+        old: StdbInventoryItem,
+        new: StdbInventoryItem,
+        event: ReducerEvent,
+    },
+    InventoryItemRemoved {
+        data: StdbInventoryItem,
+        event: ReducerEvent,
+    },
+    ChatMessageInserted {
+        data: StdbChatMessage,
+        event: ReducerEvent,
+    },
+    ZoneEventInserted {
+        data: StdbZoneEvent,
+        event: ReducerEvent,
+    },
+    /// A player's `StdbPortalJump` row was inserted or replaced.
+    PortalJumped {
+        data: StdbPortalJump,
+        event: ReducerEvent,
+    },
+}
+
+impl UncbMessage {
+    /// The variant's name, e.g. for counting messages by kind.
+    pub fn name(&self) -> &'static str {
+        match self {
+            UncbMessage::Connected { .. } => "Connected",
+            UncbMessage::Disconnected => "Disconnected",
+            UncbMessage::SubscriptionApplied => "SubscriptionApplied",
+            UncbMessage::PlayerInserted { .. } => "PlayerInserted",
+            UncbMessage::PlayerUpdated { .. } => "PlayerUpdated",
+            UncbMessage::PlayerRemoved { .. } => "PlayerRemoved",
+            UncbMessage::ObjectInserted { .. } => "ObjectInserted",
+            UncbMessage::ObjectUpdated { .. } => "ObjectUpdated",
+            UncbMessage::ObjectRemoved { .. } => "ObjectRemoved",
+            UncbMessage::InventoryItemInserted { .. } => "InventoryItemInserted",
+            UncbMessage::InventoryItemUpdated { .. } => "InventoryItemUpdated",
+            UncbMessage::InventoryItemRemoved { .. } => "InventoryItemRemoved",
+            UncbMessage::ChatMessageInserted { .. } => "ChatMessageInserted",
+            UncbMessage::ZoneEventInserted { .. } => "ZoneEventInserted",
+            UncbMessage::PortalJumped { .. } => "PortalJumped",
+        }
+    }
 }
 
-pub type UncbSend = mpsc::UnboundedSender<UncbMessage>;
 pub type UncbRecv = mpsc::UnboundedReceiver<UncbMessage>;
 
+/// The sending half of the channel `process_messages` drains, it counts every message into
+/// the backlog shared with the `UncbReceiver`.
+#[derive(Clone)]
+pub struct UncbSend {
+    send: mpsc::UnboundedSender<UncbMessage>,
+    backlog: Arc<AtomicUsize>,
+}
+
+impl UncbSend {
+    pub fn unbounded_send(
+        &self,
+        message: UncbMessage,
+    ) -> Result<(), mpsc::TrySendError<UncbMessage>> {
+        // Counted first, so the receiving side never takes a message it hasn't counted yet.
+        self.backlog.fetch_add(1, Ordering::Relaxed);
+        self.send.unbounded_send(message).map_err(|e| {
+            self.backlog.fetch_sub(1, Ordering::Relaxed);
+            e
+        })
+    }
+}
+
 #[derive(Resource)]
 pub struct UncbReceiver {
     pub recv: UncbRecv,
+    backlog: Arc<AtomicUsize>,
 }
 
 impl UncbReceiver {
-    pub fn new(recv: UncbRecv) -> Self {
-        UncbReceiver { recv }
+    /// Messages sent into the channel that `process_messages` hasn't taken out yet.
+    pub fn backlog(&self) -> usize {
+        self.backlog.load(Ordering::Relaxed)
     }
 }
 
+/// Creates the channel the SDK callbacks, or a replay, send their `UncbMessage`s through.
+pub fn channel() -> (UncbSend, UncbReceiver) {
+    let (send, recv) = mpsc::unbounded();
+    let backlog = Arc::new(AtomicUsize::new(0));
+    (
+        UncbSend {
+            send,
+            backlog: backlog.clone(),
+        },
+        UncbReceiver { recv, backlog },
+    )
+}
+
 #[derive(Event)]
 pub struct UncbEvent {
     pub message: UncbMessage,
@@ -77,6 +172,7 @@ pub fn process_messages(mut res: ResMut<UncbReceiver>, mut c: Commands) {
         let message = res.recv.try_next();
         if let Ok(message) = message {
             if let Some(message) = message {
+                res.backlog.fetch_sub(1, Ordering::Relaxed);
                 c.add(|w: &mut World| w.send_event(UncbEvent { message }));
             }
         } else {
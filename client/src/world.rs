@@ -0,0 +1,12 @@
+use bevy::math::Rect;
+
+use crate::{stdb_to_vec2, StdbWorld};
+
+/// `world_id` of the only `StdbWorld` row.
+pub const WORLD_ID: u32 = 0;
+
+/// Returns the playable area published by the server, if it has been received yet.
+pub fn world_bounds() -> Option<Rect> {
+    StdbWorld::filter_by_world_id(WORLD_ID)
+        .map(|world| Rect::from_corners(stdb_to_vec2(&world.min), stdb_to_vec2(&world.max)))
+}
@@ -0,0 +1,95 @@
+use bevy::{
+    ecs::{component::Component, system::Resource},
+    time::{Timer, TimerMode},
+};
+
+/// How long the "Entering ..." and "Leaving ..." banner stays up.
+pub const ZONE_BANNER_SECS: f32 = 3.0;
+
+/// A zone the local player is inside of.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZoneInfo {
+    pub name: String,
+    pub safe: bool,
+    pub speed_multiplier: f32,
+}
+
+/// The zones the local player is inside of, rebuilt from the `StdbZoneOccupant` rows.
+#[derive(Resource, Default)]
+pub struct CurrentZones {
+    pub zones: Vec<ZoneInfo>,
+}
+
+impl CurrentZones {
+    /// How much faster or slower the local player walks, this should match `speed_multiplier`
+    /// on the server.
+    pub fn speed_multiplier(&self) -> f32 {
+        self.zones
+            .iter()
+            .map(|zone| zone.speed_multiplier)
+            .product()
+    }
+
+    /// The zone names for the HUD, e.g. "Spawn (safe)".
+    pub fn label(&self) -> String {
+        self.zones
+            .iter()
+            .map(|zone| {
+                if zone.safe {
+                    format!("{} (safe)", zone.name)
+                } else {
+                    zone.name.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Component)]
+pub struct ZoneLabel;
+
+/// Shows which zone the local player just entered or left, cleared once `timer` finishes.
+#[derive(Component)]
+pub struct ZoneBanner {
+    pub timer: Timer,
+}
+
+impl Default for ZoneBanner {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(ZONE_BANNER_SECS, TimerMode::Once),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(name: &str, safe: bool, speed_multiplier: f32) -> ZoneInfo {
+        ZoneInfo {
+            name: name.to_string(),
+            safe,
+            speed_multiplier,
+        }
+    }
+
+    #[test]
+    fn outside_every_zone_walks_at_normal_speed() {
+        let zones = CurrentZones::default();
+
+        assert_eq!(zones.speed_multiplier(), 1.0);
+        assert_eq!(zones.label(), "");
+    }
+
+    #[test]
+    fn overlapping_zones_stack() {
+        let zones = CurrentZones {
+            zones: vec![zone("Spawn", true, 1.0), zone("Road", false, 1.5)],
+        };
+
+        assert_eq!(zones.speed_multiplier(), 1.5);
+        assert_eq!(zones.label(), "Spawn (safe), Road");
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    ecs::{
+        event::EventReader,
+        query::{With, Without},
+        system::{Commands, Query, Res, ResMut},
+    },
+    hierarchy::BuildChildren,
+    render::color::Color,
+    text::{Text, TextStyle},
+    time::Time,
+    ui::{
+        node_bundles::{NodeBundle, TextBundle},
+        AlignItems, FlexDirection, JustifyContent, PositionType, Style, Val,
+    },
+};
+use spacetimedb_sdk::table::TableType;
+
+use crate::{
+    is_local,
+    uncb_receiver::{UncbEvent, UncbMessage},
+    zone::{CurrentZones, ZoneBanner, ZoneInfo, ZoneLabel},
+    StdbZone, StdbZoneEventKind, StdbZoneOccupant,
+};
+
+pub struct ZonePlugin;
+impl Plugin for ZonePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentZones>()
+            .add_systems(Startup, (init_zone_hud,))
+            .add_systems(Update, (refresh_zones, fade_zone_banner));
+    }
+}
+
+/// Spawns the zone label and the banner below it at the top center of the screen.
+fn init_zone_hud(mut c: Commands) {
+    c.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .with_children(|parent| {
+        parent.spawn((
+            ZoneLabel,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                    ..Default::default()
+                },
+            ),
+        ));
+        parent.spawn((
+            ZoneBanner::default(),
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 28.0,
+                    color: Color::GOLD,
+                    ..Default::default()
+                },
+            ),
+        ));
+    });
+}
+
+/// Listens for the `UncbMessage::ZoneEventInserted` and `UncbMessage::SubscriptionApplied`
+/// messages, rebuilds `CurrentZones` and shows a banner when the local player entered or
+/// left a zone.
+fn refresh_zones(
+    mut current: ResMut<CurrentZones>,
+    mut labels: Query<&mut Text, (With<ZoneLabel>, Without<ZoneBanner>)>,
+    mut banners: Query<(&mut Text, &mut ZoneBanner), Without<ZoneLabel>>,
+    mut er: EventReader<UncbEvent>,
+) {
+    let mut changed = false;
+
+    for ev in er.read() {
+        match &ev.message {
+            UncbMessage::ZoneEventInserted { data, event: _ } => {
+                changed = true;
+                if !is_local(&data.client_id) {
+                    continue;
+                }
+                let Some(zone) = StdbZone::filter_by_zone_id(data.zone_id) else {
+                    continue;
+                };
+                let verb = match data.kind {
+                    StdbZoneEventKind::Enter => "Entering",
+                    StdbZoneEventKind::Exit => "Leaving",
+                };
+                for (mut text, mut banner) in &mut banners {
+                    text.sections[0].value = format!("{} {}", verb, zone.name);
+                    banner.timer.reset();
+                }
+            }
+            UncbMessage::SubscriptionApplied => changed = true,
+            _ => {}
+        }
+    }
+
+    if changed {
+        current.zones = StdbZoneOccupant::iter()
+            .filter(|occupant| is_local(&occupant.client_id))
+            .filter_map(|occupant| StdbZone::filter_by_zone_id(occupant.zone_id))
+            .map(|zone| ZoneInfo {
+                name: zone.name,
+                safe: zone.safe,
+                speed_multiplier: zone.speed_multiplier,
+            })
+            .collect();
+        for mut text in &mut labels {
+            text.sections[0].value = current.label();
+        }
+    }
+}
+
+/// Clears the banner once it's been up for `ZONE_BANNER_SECS`.
+fn fade_zone_banner(time: Res<Time>, mut q: Query<(&mut Text, &mut ZoneBanner)>) {
+    for (mut text, mut banner) in &mut q {
+        if banner.timer.tick(time.delta()).just_finished() {
+            text.sections[0].value.clear();
+        }
+    }
+}
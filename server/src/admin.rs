@@ -0,0 +1,252 @@
+use log::info;
+use spacetimedb::{spacetimedb, Identity, ReducerContext, Timestamp};
+
+use crate::{
+    cleanup_player, error::GameError, lifecycle, navigation, remove_player, store::StdbStore,
+    world, world_time, zones, StdbObject, StdbPlayer, StdbVector2,
+};
+
+/// Longest ban or mute an admin can hand out, a year.
+pub const MAX_PENALTY_SECS: u64 = 365 * 24 * 60 * 60;
+/// How long a kicked client has to wait before creating a player again.
+pub const KICK_REJOIN_SECS: u64 = 60;
+
+/// Identities allowed to call the moderation reducers.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbAdmin {
+    #[primarykey]
+    pub client_id: Identity,
+}
+
+/// A banned identity can't connect or create a player until `expires_at`.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbBan {
+    #[primarykey]
+    pub client_id: Identity,
+    pub expires_at: Timestamp,
+    pub reason: String,
+}
+
+/// A muted identity can't send chat messages until `expires_at`.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbMute {
+    #[primarykey]
+    pub client_id: Identity,
+    pub expires_at: Timestamp,
+}
+
+/// A record of every action taken through the moderation reducers.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbAuditLog {
+    #[primarykey]
+    #[autoinc]
+    pub entry_id: u64,
+    pub admin_id: Identity,
+    // The player the action was taken against, `None` for changes to the whole world.
+    pub target_id: Option<Identity>,
+    pub action: String,
+    pub details: String,
+    pub timestamp: Timestamp,
+}
+
+// Makes the identity that published the module the first admin.
+pub fn seed_admin(owner: Identity) {
+    StdbAdmin::insert(StdbAdmin { client_id: owner }).expect("Failed to insert Admin.");
+    info!("Seeded Admin: {}", owner);
+}
+
+pub fn is_banned(client_id: &Identity, now: Timestamp) -> bool {
+    StdbBan::filter_by_client_id(client_id).is_some_and(|ban| ban.expires_at > now)
+}
+
+pub fn is_muted(client_id: &Identity, now: Timestamp) -> bool {
+    StdbMute::filter_by_client_id(client_id).is_some_and(|mute| mute.expires_at > now)
+}
+
+// Removes the target's player and keeps them from creating another for `KICK_REJOIN_SECS`,
+// their client notices and leaves the game.
+#[spacetimedb(reducer)]
+pub fn kick_player(ctx: ReducerContext, target: Identity, reason: String) -> Result<(), String> {
+    require_admin(&ctx)?;
+    remove_player(target)?;
+    lifecycle::block_rejoin(
+        &mut StdbStore,
+        target,
+        after_secs(ctx.timestamp, KICK_REJOIN_SECS)?,
+    );
+
+    audit(&ctx, Some(target), "kick", reason);
+    Ok(())
+}
+
+// Kicks the target and stops them from coming back for `duration_secs`.
+#[spacetimedb(reducer)]
+pub fn ban_player(
+    ctx: ReducerContext,
+    target: Identity,
+    duration_secs: u64,
+    reason: String,
+) -> Result<(), String> {
+    require_admin(&ctx)?;
+
+    let ban = StdbBan {
+        client_id: target,
+        expires_at: after_secs(ctx.timestamp, duration_secs)?,
+        reason: reason.clone(),
+    };
+    if StdbBan::filter_by_client_id(&target).is_some() {
+        StdbBan::update_by_client_id(&target, ban);
+    } else {
+        StdbBan::insert(ban).expect("Failed to insert Ban.");
+    }
+
+    // They may not be playing right now, the ban still applies.
+    cleanup_player(target);
+
+    audit(
+        &ctx,
+        Some(target),
+        "ban",
+        format!("{}s: {}", duration_secs, reason),
+    );
+    Ok(())
+}
+
+// Stops the target from sending chat messages for `duration_secs`.
+#[spacetimedb(reducer)]
+pub fn mute_player(
+    ctx: ReducerContext,
+    target: Identity,
+    duration_secs: u64,
+) -> Result<(), String> {
+    require_admin(&ctx)?;
+
+    let mute = StdbMute {
+        client_id: target,
+        expires_at: after_secs(ctx.timestamp, duration_secs)?,
+    };
+    if StdbMute::filter_by_client_id(&target).is_some() {
+        StdbMute::update_by_client_id(&target, mute);
+    } else {
+        StdbMute::insert(mute).expect("Failed to insert Mute.");
+    }
+
+    audit(&ctx, Some(target), "mute", format!("{}s", duration_secs));
+    Ok(())
+}
+
+// Moves the target's player object to `position`.
+#[spacetimedb(reducer)]
+pub fn teleport_player(
+    ctx: ReducerContext,
+    target: Identity,
+    position: StdbVector2,
+) -> Result<(), String> {
+    require_admin(&ctx)?;
+
+    let player = StdbPlayer::filter_by_client_id(&target).ok_or(GameError::NotFound("Player"))?;
+    let mut object = StdbObject::filter_by_object_id(&player.object_id)
+        .ok_or(GameError::NotFound("Player object"))?;
+    world::place_object(&mut object, world::clamp_to_bounds(position), ctx.timestamp);
+    navigation::cancel_path(player.object_id);
+    zones::refresh_occupancy(target, &object, ctx.timestamp);
+    let details = format!("({}, {})", object.position.x, object.position.y);
+    StdbObject::update_by_object_id(&player.object_id, object);
+
+    audit(&ctx, Some(target), "teleport", details);
+    Ok(())
+}
+
+// Places a prop of `kind` at `position` in the caller's room, paths are found again around it.
+#[spacetimedb(reducer)]
+pub fn place_prop(ctx: ReducerContext, kind: String, position: StdbVector2) -> Result<(), String> {
+    require_admin(&ctx)?;
+    if navigation::solid_radius(&kind).is_none() {
+        return Err(GameError::Invalid(format!("{} isn't a prop", kind)).into());
+    }
+    let player =
+        StdbPlayer::filter_by_client_id(&ctx.sender).ok_or(GameError::NotFound("Player"))?;
+
+    let position = world::clamp_to_bounds(position);
+    let details = format!("{} at ({}, {})", kind, position.x, position.y);
+    world::insert_prop(&kind, position, player.room_id);
+    navigation::rebuild_grid(player.room_id);
+
+    audit(&ctx, None, "place prop", details);
+    Ok(())
+}
+
+// Removes the prop with `object_id`, paths are found again through where it was.
+#[spacetimedb(reducer)]
+pub fn remove_prop(ctx: ReducerContext, object_id: u64) -> Result<(), String> {
+    require_admin(&ctx)?;
+    let object = StdbObject::filter_by_object_id(&object_id)
+        .filter(|object| navigation::solid_radius(&object.kind).is_some())
+        .ok_or(GameError::NotFound("Prop"))?;
+
+    StdbObject::delete_by_object_id(&object_id);
+    navigation::rebuild_grid(object.room_id);
+
+    audit(
+        &ctx,
+        None,
+        "remove prop",
+        format!(
+            "{} at ({}, {})",
+            object.kind, object.position.x, object.position.y
+        ),
+    );
+    Ok(())
+}
+
+// Changes how long a day lasts for everyone, without skipping ahead or back in the day.
+#[spacetimedb(reducer)]
+pub fn set_day_length(ctx: ReducerContext, secs: u64) -> Result<(), String> {
+    require_admin(&ctx)?;
+    world_time::set_day_length(secs, ctx.timestamp)?;
+
+    audit(&ctx, None, "day length", format!("{}s", secs));
+    Ok(())
+}
+
+fn require_admin(ctx: &ReducerContext) -> Result<(), GameError> {
+    if StdbAdmin::filter_by_client_id(&ctx.sender).is_none() {
+        return Err(GameError::NotAllowed("Only admins can do that"));
+    }
+    Ok(())
+}
+
+fn audit(ctx: &ReducerContext, target_id: Option<Identity>, action: &str, details: String) {
+    match target_id {
+        Some(target_id) => info!("Admin {} {} {}: {}", ctx.sender, action, target_id, details),
+        None => info!("Admin {} {}: {}", ctx.sender, action, details),
+    }
+    StdbAuditLog::insert(StdbAuditLog {
+        entry_id: 0,
+        admin_id: ctx.sender,
+        target_id,
+        action: action.to_string(),
+        details,
+        timestamp: ctx.timestamp,
+    })
+    .expect("Failed to insert Audit Log.");
+}
+
+// When a ban or mute of `secs` starting at `timestamp` runs out.
+fn after_secs(timestamp: Timestamp, secs: u64) -> Result<Timestamp, GameError> {
+    if secs > MAX_PENALTY_SECS {
+        return Err(GameError::Invalid(format!(
+            "Bans and mutes can last at most {} days",
+            MAX_PENALTY_SECS / (24 * 60 * 60)
+        )));
+    }
+    let micros = secs
+        .checked_mul(1_000_000)
+        .and_then(|micros| timestamp.into_micros_since_epoch().checked_add(micros))
+        .ok_or_else(|| GameError::Invalid("Duration is too long".to_string()))?;
+    Ok(Timestamp::from_micros_since_epoch(micros))
+}
@@ -0,0 +1,54 @@
+use log::info;
+use spacetimedb::{spacetimedb, ReducerContext, SpacetimeType};
+
+use crate::{error::GameError, StdbClient, StdbPlayer};
+
+/// Every color a player is allowed to pick, the client maps each name to an actual color.
+pub const ALLOWED_COLORS: [&str; 8] = [
+    "white", "red", "orange", "yellow", "green", "blue", "purple", "pink",
+];
+/// Every skin a player is allowed to pick, the client maps each name to a texture.
+pub const ALLOWED_SKINS: [&str; 4] = ["default", "knight", "mage", "rogue"];
+
+#[derive(SpacetimeType, Clone)]
+pub struct StdbAppearance {
+    pub color: String,
+    pub skin: String,
+}
+
+impl Default for StdbAppearance {
+    fn default() -> Self {
+        Self {
+            color: ALLOWED_COLORS[0].to_string(),
+            skin: ALLOWED_SKINS[0].to_string(),
+        }
+    }
+}
+
+// Called by the client when they pick a new color or skin. The appearance is saved on
+// the `StdbClient` so it's kept between sessions, and copied onto the current `StdbPlayer`.
+#[spacetimedb(reducer)]
+pub fn set_appearance(ctx: ReducerContext, color: String, skin: String) -> Result<(), String> {
+    if !ALLOWED_COLORS.contains(&color.as_str()) {
+        return Err(GameError::Invalid(format!("Color {} isn't allowed", color)).into());
+    }
+    if !ALLOWED_SKINS.contains(&skin.as_str()) {
+        return Err(GameError::Invalid(format!("Skin {} isn't allowed", skin)).into());
+    }
+
+    let appearance = StdbAppearance { color, skin };
+
+    let mut client =
+        StdbClient::filter_by_client_id(&ctx.sender).ok_or(GameError::NotFound("Client"))?;
+    client.appearance = appearance.clone();
+    StdbClient::update_by_client_id(&ctx.sender, client);
+
+    if let Some(mut player) = StdbPlayer::filter_by_client_id(&ctx.sender) {
+        player.appearance = appearance;
+        let object_id = player.object_id;
+        StdbPlayer::update_by_object_id(&object_id, player);
+    }
+
+    info!("Updated Appearance");
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use log::info;
+use spacetimedb::{spacetimedb, Identity, ReducerContext, Timestamp};
+
+use crate::{admin, error::GameError, teams, StdbPlayer};
+
+/// Longest message, in characters, that `send_chat_message` accepts.
+pub const CHAT_MESSAGE_MAX_LEN: usize = 200;
+/// How many messages are kept for each recipient, older ones are deleted as new ones arrive.
+pub const CHAT_HISTORY: usize = 100;
+
+/// A chat message as one recipient gets it. Messages to everyone are stored once for
+/// `everyone()`, team messages once for every member of the team, so clients only subscribe
+/// to their own rows and other teams' messages never reach them.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbChatMessage {
+    #[primarykey]
+    #[autoinc]
+    pub message_id: u64,
+    pub sender_id: Identity,
+    // `everyone()` or the client the message was delivered to.
+    pub recipient_id: Identity,
+    // `teams::NO_TEAM` for messages to everyone, otherwise the sender's team.
+    pub team_id: u64,
+    pub text: String,
+    pub sent_at: Timestamp,
+}
+
+// The `recipient_id` of messages to everyone, no client has this identity.
+pub fn everyone() -> Identity {
+    Identity::from_byte_array([0; 32])
+}
+
+// Called by the client when they send a message from the chat box.
+#[spacetimedb(reducer)]
+pub fn send_chat_message(ctx: ReducerContext, text: String) -> Result<(), String> {
+    post_message(ctx, text, teams::NO_TEAM, vec![everyone()])
+}
+
+// Called by the client when they send a message to their own team, it's delivered to the
+// team's members in the sender's room.
+#[spacetimedb(reducer)]
+pub fn send_team_message(ctx: ReducerContext, text: String) -> Result<(), String> {
+    let player =
+        StdbPlayer::filter_by_client_id(&ctx.sender).ok_or(GameError::NotFound("Player"))?;
+    if player.team_id == teams::NO_TEAM {
+        return Err(GameError::Invalid("You aren't on a team".to_string()).into());
+    }
+    let recipients = StdbPlayer::filter_by_room_id(&player.room_id)
+        .filter(|member| member.team_id == player.team_id)
+        .map(|member| member.client_id)
+        .collect();
+    post_message(ctx, text, player.team_id, recipients)
+}
+
+fn post_message(
+    ctx: ReducerContext,
+    text: String,
+    team_id: u64,
+    recipients: Vec<Identity>,
+) -> Result<(), String> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(GameError::Invalid("Message is empty".to_string()).into());
+    }
+    if text.chars().count() > CHAT_MESSAGE_MAX_LEN {
+        return Err(GameError::Invalid("Message is too long".to_string()).into());
+    }
+    if admin::is_muted(&ctx.sender, ctx.timestamp) {
+        return Err(GameError::Muted.into());
+    }
+
+    for recipient_id in recipients {
+        StdbChatMessage::insert(StdbChatMessage {
+            message_id: 0,
+            sender_id: ctx.sender,
+            recipient_id,
+            team_id,
+            text: text.clone(),
+            sent_at: ctx.timestamp,
+        })
+        .expect("Failed to insert Chat Message.");
+        trim_history(recipient_id);
+    }
+
+    info!("Chat Message sent by {}", ctx.sender);
+    Ok(())
+}
+
+// Deletes the recipient's oldest messages until only `CHAT_HISTORY` remain.
+fn trim_history(recipient_id: Identity) {
+    let mut message_ids: Vec<u64> = StdbChatMessage::filter_by_recipient_id(&recipient_id)
+        .map(|message| message.message_id)
+        .collect();
+    if message_ids.len() <= CHAT_HISTORY {
+        return;
+    }
+
+    message_ids.sort_unstable();
+    for message_id in &message_ids[..message_ids.len() - CHAT_HISTORY] {
+        StdbChatMessage::delete_by_message_id(message_id);
+    }
+}
@@ -0,0 +1,37 @@
+use spacetimedb::{spacetimedb, Identity, ReducerContext};
+
+/// The last clock sample of each client, answering its latest `sync_clock` call.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbClockSync {
+    #[primarykey]
+    pub client_id: Identity,
+    // The client's own clock when it called `sync_clock`, handed back so it can tell how
+    // long the answer took.
+    pub client_sent_micros: u64,
+    // When the server ran `sync_clock`, in microseconds since the unix epoch.
+    pub server_micros: u64,
+}
+
+// Called by the client every few seconds to estimate the offset between its clock and
+// the server's, and the round trip time to the server.
+#[spacetimedb(reducer)]
+pub fn sync_clock(ctx: ReducerContext, client_sent_micros: u64) -> Result<(), String> {
+    let sync = StdbClockSync {
+        client_id: ctx.sender,
+        client_sent_micros,
+        server_micros: ctx.timestamp.into_micros_since_epoch(),
+    };
+
+    if StdbClockSync::filter_by_client_id(&ctx.sender).is_some() {
+        StdbClockSync::update_by_client_id(&ctx.sender, sync);
+    } else {
+        StdbClockSync::insert(sync).expect("Failed to insert ClockSync.");
+    }
+    Ok(())
+}
+
+// Forgets the client's last sample, so it doesn't outlive the session it was taken in.
+pub fn end_sync(client_id: Identity) {
+    StdbClockSync::delete_by_client_id(&client_id);
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+
+/// Why a reducer or one of its helpers failed. Reducers still return `Result<(), String>`,
+/// `?` turns a `GameError` into the message the client sees.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameError {
+    /// A row the caller needs doesn't exist, e.g. `NotFound("Player")`.
+    NotFound(&'static str),
+    /// A row the caller tried to create is already there.
+    AlreadyExists(&'static str),
+    Banned,
+    /// The caller was kicked a moment ago and can't rejoin yet.
+    Kicked,
+    Muted,
+    /// The caller isn't allowed to do this, with the reason.
+    NotAllowed(&'static str),
+    /// The arguments or the current state don't allow this, with the reason.
+    Invalid(String),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::NotFound(what) => write!(f, "{} not found", what),
+            GameError::AlreadyExists(what) => write!(f, "{} already exists", what),
+            GameError::Banned => write!(f, "You are banned"),
+            GameError::Kicked => write!(f, "You were kicked, try again later"),
+            GameError::Muted => write!(f, "You are muted"),
+            GameError::NotAllowed(reason) => write!(f, "{}", reason),
+            GameError::Invalid(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl From<GameError> for String {
+    fn from(error: GameError) -> Self {
+        error.to_string()
+    }
+}
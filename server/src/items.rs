@@ -0,0 +1,184 @@
+use log::info;
+use spacetimedb::{spacetimedb, Identity, ReducerContext};
+
+use crate::{
+    error::GameError, matches, rooms, StdbObject, StdbPlayer, StdbVector2, OBJECT_KIND_ITEM,
+};
+
+/// The furthest a player's object can be from an item drop and still pick it up.
+pub const PICKUP_RANGE: f32 = 75.0;
+/// The number of separate stacks a single inventory can hold.
+pub const INVENTORY_SIZE: usize = 16;
+
+/// Definition of an item that can exist in the world or in an inventory.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbItem {
+    #[primarykey]
+    #[autoinc]
+    pub item_id: u64,
+    #[unique]
+    pub name: String,
+    pub max_stack: u32,
+}
+
+/// An item lying in the world, it shares its `object_id` with the `StdbObject` that positions it.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbItemDrop {
+    #[primarykey]
+    pub object_id: u64,
+    pub item_id: u64,
+    pub quantity: u32,
+}
+
+/// A single stack inside a client's inventory. Stacks are keyed by identity rather
+/// than by player so they outlive the player object between sessions.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbInventoryItem {
+    #[primarykey]
+    #[autoinc]
+    pub entry_id: u64,
+    pub client_id: Identity,
+    pub item_id: u64,
+    pub quantity: u32,
+}
+
+// Inserts the item definitions and a few drops to start the world with.
+pub fn seed_items() {
+    let items = [("Coin", 99), ("Gem", 10), ("Key", 1)];
+    for (name, max_stack) in items {
+        StdbItem::insert(StdbItem {
+            item_id: 0,
+            name: name.to_string(),
+            max_stack,
+        })
+        .expect("Failed to insert Item.");
+    }
+
+    let drops = [
+        ("Coin", 25, StdbVector2 { x: 150.0, y: 100.0 }),
+        ("Coin", 120, StdbVector2 { x: -200.0, y: 50.0 }),
+        ("Gem", 3, StdbVector2 { x: 0.0, y: -250.0 }),
+        (
+            "Key",
+            1,
+            StdbVector2 {
+                x: 300.0,
+                y: -150.0,
+            },
+        ),
+    ];
+    for (name, quantity, position) in drops {
+        if let Some(item) = StdbItem::filter_by_name(&name.to_string()) {
+            spawn_item_drop(&item, quantity, position, rooms::LOBBY_ROOM_ID);
+        }
+    }
+    info!("Seeded Items");
+}
+
+// Creates the `StdbObject` and `StdbItemDrop` rows for an item lying in the room with `room_id`.
+pub fn spawn_item_drop(item: &StdbItem, quantity: u32, position: StdbVector2, room_id: u64) -> u64 {
+    let object_id = StdbObject::insert(StdbObject {
+        object_id: 0,
+        name: item.name.clone(),
+        kind: OBJECT_KIND_ITEM.to_string(),
+        room_id,
+        position,
+        ..Default::default()
+    })
+    .expect("Failed to create a unique Item Drop.")
+    .object_id;
+
+    StdbItemDrop::insert(StdbItemDrop {
+        object_id,
+        item_id: item.item_id,
+        quantity,
+    })
+    .expect("Failed to insert Item Drop.");
+
+    object_id
+}
+
+// Called by the client when they try to pick up the drop with the given `object_id`.
+// Whatever doesn't fit in the inventory is left on the ground.
+#[spacetimedb(reducer)]
+pub fn pickup_item(ctx: ReducerContext, object_id: u64) -> Result<(), String> {
+    let player =
+        StdbPlayer::filter_by_client_id(&ctx.sender).ok_or(GameError::NotFound("Player"))?;
+    let player_object = StdbObject::filter_by_object_id(&player.object_id)
+        .ok_or(GameError::NotFound("Player object"))?;
+    let mut drop =
+        StdbItemDrop::filter_by_object_id(&object_id).ok_or(GameError::NotFound("Item drop"))?;
+    let drop_object = StdbObject::filter_by_object_id(&object_id)
+        .ok_or(GameError::NotFound("Item drop object"))?;
+
+    if player_object.room_id != drop_object.room_id
+        || player_object.position.distance(&drop_object.position) > PICKUP_RANGE
+    {
+        return Err(GameError::Invalid("Item drop is out of range".to_string()).into());
+    }
+
+    let item = StdbItem::filter_by_item_id(&drop.item_id).ok_or(GameError::NotFound("Item"))?;
+    let remaining = add_to_inventory(ctx.sender, &item, drop.quantity);
+    if remaining == drop.quantity {
+        return Err(GameError::Invalid("Inventory is full".to_string()).into());
+    }
+
+    let picked_up = drop.quantity - remaining;
+    info!("Picked up {} {}", picked_up, item.name);
+    matches::add_score(ctx.sender, player.room_id, picked_up);
+
+    if remaining == 0 {
+        StdbItemDrop::delete_by_object_id(&object_id);
+        StdbObject::delete_by_object_id(&object_id);
+    } else {
+        drop.quantity = remaining;
+        StdbItemDrop::update_by_object_id(&object_id, drop);
+    }
+
+    Ok(())
+}
+
+// Tops up the client's existing stacks of `item` before opening new ones,
+// returns the quantity that didn't fit.
+fn add_to_inventory(client_id: Identity, item: &StdbItem, quantity: u32) -> u32 {
+    if item.max_stack == 0 {
+        return quantity;
+    }
+
+    let mut remaining = quantity;
+    let entries: Vec<StdbInventoryItem> =
+        StdbInventoryItem::filter_by_client_id(&client_id).collect();
+
+    for mut entry in entries.iter().cloned() {
+        if remaining == 0 {
+            break;
+        }
+        if entry.item_id != item.item_id || entry.quantity >= item.max_stack {
+            continue;
+        }
+        let added = remaining.min(item.max_stack - entry.quantity);
+        entry.quantity += added;
+        remaining -= added;
+        let entry_id = entry.entry_id;
+        StdbInventoryItem::update_by_entry_id(&entry_id, entry);
+    }
+
+    let mut stacks = entries.len();
+    while remaining > 0 && stacks < INVENTORY_SIZE {
+        let added = remaining.min(item.max_stack);
+        StdbInventoryItem::insert(StdbInventoryItem {
+            entry_id: 0,
+            client_id,
+            item_id: item.item_id,
+            quantity: added,
+        })
+        .expect("Failed to insert Inventory Item.");
+        remaining -= added;
+        stacks += 1;
+    }
+
+    remaining
+}
@@ -1,5 +1,32 @@
-use log::info;
-use spacetimedb::{spacetimedb, Identity, ReducerContext, Result, SpacetimeType};
+use appearance::StdbAppearance;
+use error::GameError;
+use spacetimedb::{spacetimedb, Identity, ReducerContext, Result, SpacetimeType, Timestamp};
+use store::StdbStore;
+
+pub mod admin;
+pub mod appearance;
+pub mod chat;
+pub mod clock;
+pub mod error;
+pub mod items;
+pub mod lifecycle;
+pub mod matches;
+#[cfg(test)]
+pub mod memory_store;
+pub mod navigation;
+pub mod npcs;
+pub mod ping;
+pub mod portals;
+pub mod rooms;
+pub mod stats;
+pub mod store;
+pub mod teams;
+pub mod world;
+pub mod world_time;
+pub mod zones;
+
+pub const OBJECT_KIND_PLAYER: &str = "player";
+pub const OBJECT_KIND_ITEM: &str = "item";
 
 #[spacetimedb(table)]
 #[derive(Clone)]
@@ -7,6 +34,10 @@ pub struct StdbClient {
     #[primarykey]
     pub client_id: Identity,
     pub connected: bool,
+    // The last appearance this client picked, copied onto every new `StdbPlayer`.
+    pub appearance: StdbAppearance,
+    // A kicked client can't create a player again until then.
+    pub kicked_until: Timestamp,
 }
 
 #[derive(SpacetimeType, Clone, Default)]
@@ -15,6 +46,12 @@ pub struct StdbVector2 {
     pub y: f32,
 }
 
+impl StdbVector2 {
+    pub fn distance(&self, other: &StdbVector2) -> f32 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+}
+
 #[spacetimedb(table)]
 #[derive(Clone, Default)]
 pub struct StdbObject {
@@ -22,8 +59,19 @@ pub struct StdbObject {
     #[autoinc]
     pub object_id: u64,
     pub name: String,
+    // Tells the client how to render this object, e.g. `OBJECT_KIND_PLAYER`.
+    pub kind: String,
+    // Objects only share a space with objects in the same room, see `rooms::LOBBY_ROOM_ID`.
+    pub room_id: u64,
 
     pub position: StdbVector2,
+    // Units per second the object moved at with its last move, clients keep it moving at
+    // this speed until the next update, see `world::move_object`.
+    pub velocity: StdbVector2,
+    // The direction of the last move that went anywhere, in radians counterclockwise from +x.
+    pub facing: f32,
+    // When the object last moved, in microseconds since the unix epoch.
+    pub moved_at_micros: u64,
 }
 
 #[spacetimedb(table)]
@@ -34,48 +82,85 @@ pub struct StdbPlayer {
 
     #[unique]
     pub client_id: Identity,
+
+    pub appearance: StdbAppearance,
+    // Always the same as the `room_id` of the player's object.
+    pub room_id: u64,
+    // Players are kept balanced across the teams of each room, see `teams::balanced_team`.
+    pub team_id: u64,
 }
 
 #[spacetimedb(init)]
-pub fn init() {
+pub fn init(ctx: ReducerContext) {
     // Called when the module is initially published
+    admin::seed_admin(ctx.sender);
+    world::seed_world();
+    world_time::seed_world_time(ctx.timestamp);
+    teams::seed_teams();
+    items::seed_items();
+    world::seed_props();
+    npcs::seed_npcs();
+    zones::seed_zones();
+    portals::seed_portals();
+    navigation::rebuild_grid(rooms::LOBBY_ROOM_ID);
+
+    spacetimedb::schedule!("100ms", tick(_, Timestamp::now()));
+}
+
+// Runs every 100ms for as long as the module is published, everything the
+// server simulates on its own is advanced from here.
+#[spacetimedb(reducer)]
+pub fn tick(ctx: ReducerContext, prev_time: Timestamp) {
+    ping::expire_pings(ctx.timestamp);
+    world_time::advance_world_time(ctx.timestamp);
+    zones::expire_zone_events(ctx.timestamp);
+    matches::advance_matches(ctx.timestamp);
+    // NPCs and paths read the grid of their room, each grid is only loaded once a tick.
+    let mut grids = navigation::NavGrids::default();
+    npcs::advance_npcs(ctx.timestamp, prev_time, &mut grids);
+    navigation::advance_paths(ctx.timestamp, prev_time, &mut grids);
+    stats::refresh_leaderboard(ctx.timestamp);
+
+    spacetimedb::schedule!("100ms", tick(_, ctx.timestamp));
 }
 
 // Called when the client connects, we update the logged_in state to true
 #[spacetimedb(connect)]
-pub fn client_connected(ctx: ReducerContext) {
+pub fn client_connected(ctx: ReducerContext) -> Result<(), String> {
+    // Banned clients are turned away before they're marked as connected
+    if admin::is_banned(&ctx.sender, ctx.timestamp) {
+        log::info!("Rejected banned Client: {}", ctx.sender);
+        return Err(GameError::Banned.into());
+    }
+
     // called when the client connects, we update the logged_in state to true
-    update_client_login_state(ctx, true);
+    stats::start_session(ctx.sender, ctx.timestamp);
+    update_client_login_state(ctx, true)?;
+    Ok(())
 }
 
 // Called when the client disconnects, we update the logged_in state to false
 #[spacetimedb(disconnect)]
 pub fn client_disconnected(ctx: ReducerContext) {
     // Called when the client disconnects, we update the logged_in state to false
-    update_client_login_state(ctx, false);
+    stats::end_session(ctx.sender, ctx.timestamp);
+    clock::end_sync(ctx.sender);
+    // Disconnecting can't be refused, so whatever went wrong is only logged.
+    if let Err(e) = update_client_login_state(ctx, false) {
+        log::warn!("Failed to disconnect Client {}: {}", ctx.sender, e);
+    }
 }
 
-// This helper function gets the PlayerComponent, sets the logged
-// in variable and updates the PlayerComponent table row.
-pub fn update_client_login_state(ctx: ReducerContext, connected: bool) {
-    if let Some(client) = StdbClient::filter_by_client_id(&ctx.sender) {
-        // We clone the PlayerComponent so we can edit it and pass it back.
-        let mut client: StdbClient = client.clone();
-        client.connected = connected;
-        StdbClient::update_by_client_id(&ctx.sender, client);
-
-        if !connected {
-            remove_player(ctx).expect("Player doesn't exist");
-        }
-        info!("Updated Client Login State");
-    } else {
-        StdbClient::insert(StdbClient {
-            client_id: ctx.sender,
-            connected,
-        })
-        .expect("Failed to create a unique Client");
-        info!("Created Client");
+// This helper function sets the connected state of the client, see
+// `lifecycle::update_client_login_state`.
+pub fn update_client_login_state(ctx: ReducerContext, connected: bool) -> Result<(), GameError> {
+    let removed = lifecycle::update_client_login_state(&mut StdbStore, ctx.sender, connected)?;
+    if let Some(player) = removed {
+        zones::forget_client(player.client_id);
+        portals::forget_client(player.client_id);
+        rooms::close_room_if_empty(player.room_id);
     }
+    Ok(())
 }
 
 // This reducer is called when the user logs in for the first time and
@@ -85,56 +170,54 @@ pub fn create_player(ctx: ReducerContext) -> Result<(), String> {
     // Get the Identity of the client who called this reducer
     let client_id = ctx.sender;
 
-    // Make sure we don't already have a player with this identity
-    if StdbPlayer::filter_by_client_id(&client_id).is_some() {
-        log::info!("Player already exists");
-        return Err("Player already exists".to_string());
+    if admin::is_banned(&client_id, ctx.timestamp) {
+        return Err(GameError::Banned.into());
     }
 
-    // Create a new entity for this player and get a unique `entity_id`.
-    let object_id = StdbObject::insert(StdbObject::default())
-        .expect("Failed to create a unique Player.")
-        .object_id;
-
-    // The PlayerComponent uses the same entity_id and stores the identity of
-    // the owner, username, and whether or not they are logged in.
-    StdbPlayer::insert(StdbPlayer {
-        object_id,
-        client_id,
-    })
-    .expect("Failed to insert Player.");
-
-    log::info!("Player created: {}", object_id);
-
+    let team_id = teams::balanced_team(rooms::LOBBY_ROOM_ID);
+    lifecycle::create_player(&mut StdbStore, client_id, team_id, ctx.timestamp)?;
     Ok(())
 }
 
-pub fn remove_player(ctx: ReducerContext) -> Result<(), String> {
-    let client_id = ctx.sender;
-
-    if !StdbPlayer::filter_by_client_id(&client_id).is_some() {
-        log::info!("Player doesn't exist");
-        return Err("Player doesn't exist".to_string());
-    }
+// Removes the client's player and object, and closes their room if it's now empty.
+pub fn remove_player(client_id: Identity) -> Result<(), GameError> {
+    let player = lifecycle::remove_player(&mut StdbStore, client_id)?;
+    zones::forget_client(client_id);
+    portals::forget_client(client_id);
+    rooms::close_room_if_empty(player.room_id);
+    Ok(())
+}
 
-    if let Some(player) = StdbPlayer::filter_by_client_id(&ctx.sender) {
-        let _player = player.clone();
-        StdbPlayer::delete_by_client_id(&client_id);
-        log::info!("Removed Player: {}", _player.client_id);
+// Like `remove_player`, but for when the client may not have a player.
+pub fn cleanup_player(client_id: Identity) {
+    if let Some(player) = lifecycle::cleanup_player(&mut StdbStore, client_id) {
+        zones::forget_client(client_id);
+        portals::forget_client(client_id);
+        rooms::close_room_if_empty(player.room_id);
     }
-
-    Ok(())
 }
 
 #[spacetimedb(reducer)]
 pub fn update_player_pos(ctx: ReducerContext, position: StdbVector2) -> Result<(), String> {
     if let Some(player) = StdbPlayer::filter_by_client_id(&ctx.sender) {
         if let Some(mut object) = StdbObject::filter_by_object_id(&player.object_id) {
-            object.position = position;
+            let position = world::clamp_to_bounds(position);
+            // The client hasn't seen its portal jump yet, moving it back would undo the jump.
+            if portals::last_jump(ctx.sender)
+                .is_some_and(|jump| jump.is_stale(&position, ctx.timestamp))
+            {
+                return Ok(());
+            }
+            stats::add_distance(ctx.sender, object.position.distance(&position));
+            // Moving by hand stops walking to where the player clicked.
+            navigation::cancel_path(player.object_id);
+            world::move_object(&mut object, position, ctx.timestamp);
+            portals::enter_portal(ctx.sender, &mut object, ctx.timestamp);
+            zones::refresh_occupancy(ctx.sender, &object, ctx.timestamp);
             StdbObject::update_by_object_id(&player.object_id, object);
             return Ok(());
         }
     }
 
-    return Err("Player not found".to_string());
+    Err(GameError::NotFound("Player").into())
 }
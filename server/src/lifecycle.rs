@@ -0,0 +1,288 @@
+use log::{info, warn};
+use spacetimedb::{Identity, Timestamp};
+
+use crate::{
+    appearance::StdbAppearance, error::GameError, rooms, store::Store, StdbClient, StdbObject,
+    StdbPlayer, OBJECT_KIND_PLAYER,
+};
+
+// Marks the client as connected or disconnected, creating the client the first time it
+// connects. Disconnecting also cleans up the client's player with `cleanup_player`, and
+// returns it so the caller can clean up after it.
+pub fn update_client_login_state(
+    store: &mut impl Store,
+    client_id: Identity,
+    connected: bool,
+) -> Result<Option<StdbPlayer>, GameError> {
+    if let Some(mut client) = store.client(&client_id) {
+        client.connected = connected;
+        store.update_client(client);
+        info!("Updated Client Login State");
+    } else {
+        if !connected {
+            warn!("Client disconnected without connecting: {}", client_id);
+        }
+        store.insert_client(StdbClient {
+            client_id,
+            connected,
+            appearance: StdbAppearance::default(),
+            kicked_until: Timestamp::from_micros_since_epoch(0),
+        })?;
+        info!("Created Client");
+    }
+
+    if connected {
+        return Ok(None);
+    }
+    Ok(cleanup_player(store, client_id))
+}
+
+// Creates the client's player in the lobby on `team_id`, along with the object it moves.
+// Every client has at most one player, and a kicked client has to wait to create another.
+pub fn create_player(
+    store: &mut impl Store,
+    client_id: Identity,
+    team_id: u64,
+    now: Timestamp,
+) -> Result<StdbPlayer, GameError> {
+    // Make sure we don't already have a player with this identity
+    if store.player(&client_id).is_some() {
+        info!("Player already exists");
+        return Err(GameError::AlreadyExists("Player"));
+    }
+    if store
+        .client(&client_id)
+        .is_some_and(|client| client.kicked_until > now)
+    {
+        return Err(GameError::Kicked);
+    }
+
+    let object_id = store.insert_object(StdbObject {
+        kind: OBJECT_KIND_PLAYER.to_string(),
+        ..Default::default()
+    })?;
+
+    // Players look the same as they did last session.
+    let appearance = store
+        .client(&client_id)
+        .map(|client| client.appearance)
+        .unwrap_or_default();
+
+    let player = StdbPlayer {
+        object_id,
+        client_id,
+        appearance,
+        room_id: rooms::LOBBY_ROOM_ID,
+        team_id,
+    };
+    store.insert_player(player.clone())?;
+
+    info!("Player created: {}", object_id);
+    Ok(player)
+}
+
+// Removes the client's player like `cleanup_player`, but it's an error if there's none.
+pub fn remove_player(store: &mut impl Store, client_id: Identity) -> Result<StdbPlayer, GameError> {
+    cleanup_player(store, client_id).ok_or(GameError::NotFound("Player"))
+}
+
+// Stops the client creating a player again until `until`, their player is removed separately.
+pub fn block_rejoin(store: &mut impl Store, client_id: Identity, until: Timestamp) {
+    if let Some(mut client) = store.client(&client_id) {
+        client.kicked_until = until;
+        store.update_client(client);
+    }
+}
+
+// Deletes the client's player and the player's object, whichever of them exist, and returns
+// the player if there was one. A client that never created a player, or whose player was
+// already kicked, is expected here and only logged.
+pub fn cleanup_player(store: &mut impl Store, client_id: Identity) -> Option<StdbPlayer> {
+    let Some(player) = store.player(&client_id) else {
+        warn!("No Player to remove for Client: {}", client_id);
+        return None;
+    };
+
+    store.delete_player(&client_id);
+    if !store.delete_object(player.object_id) {
+        warn!("Player {} had no object to remove", player.object_id);
+    }
+
+    info!("Removed Player: {}", client_id);
+    Some(player)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_store::MemoryStore;
+
+    const TEAM_ID: u64 = 1;
+
+    fn identity(n: u8) -> Identity {
+        Identity::from_byte_array([n; 32])
+    }
+
+    fn at(secs: u64) -> Timestamp {
+        Timestamp::from_micros_since_epoch(secs * 1_000_000)
+    }
+
+    fn connected_store(client_id: Identity) -> MemoryStore {
+        let mut store = MemoryStore::default();
+        update_client_login_state(&mut store, client_id, true).unwrap();
+        store
+    }
+
+    #[test]
+    fn first_connection_creates_client() {
+        let store = connected_store(identity(1));
+
+        let client = store.client(&identity(1)).unwrap();
+        assert!(client.connected);
+        assert!(store.players.is_empty());
+    }
+
+    #[test]
+    fn creates_player_with_object_in_lobby() {
+        let mut store = connected_store(identity(1));
+
+        let player = create_player(&mut store, identity(1), TEAM_ID, at(10)).unwrap();
+
+        assert_eq!(player.room_id, rooms::LOBBY_ROOM_ID);
+        assert_eq!(player.team_id, TEAM_ID);
+        let object = store.object(player.object_id).unwrap();
+        assert_eq!(object.kind, OBJECT_KIND_PLAYER);
+        assert_eq!(object.room_id, player.room_id);
+    }
+
+    #[test]
+    fn rejects_duplicate_player() {
+        let mut store = connected_store(identity(1));
+        create_player(&mut store, identity(1), TEAM_ID, at(10)).unwrap();
+
+        let result = create_player(&mut store, identity(1), TEAM_ID, at(10));
+
+        assert_eq!(result.err(), Some(GameError::AlreadyExists("Player")));
+        assert_eq!(store.players.len(), 1);
+    }
+
+    #[test]
+    fn players_get_their_own_objects() {
+        let mut store = connected_store(identity(1));
+        update_client_login_state(&mut store, identity(2), true).unwrap();
+
+        let first = create_player(&mut store, identity(1), TEAM_ID, at(10)).unwrap();
+        let second = create_player(&mut store, identity(2), TEAM_ID, at(10)).unwrap();
+
+        assert_ne!(first.object_id, second.object_id);
+    }
+
+    #[test]
+    fn disconnect_removes_player() {
+        let mut store = connected_store(identity(1));
+        let player = create_player(&mut store, identity(1), TEAM_ID, at(10)).unwrap();
+
+        let removed = update_client_login_state(&mut store, identity(1), false).unwrap();
+
+        assert_eq!(removed.map(|p| p.object_id), Some(player.object_id));
+        assert!(!store.client(&identity(1)).unwrap().connected);
+        assert!(store.player(&identity(1)).is_none());
+        assert!(store.object(player.object_id).is_none());
+    }
+
+    #[test]
+    fn reconnect_creates_new_player_with_same_appearance() {
+        let mut store = connected_store(identity(1));
+        let mut client = store.client(&identity(1)).unwrap();
+        client.appearance = StdbAppearance {
+            color: "red".to_string(),
+            skin: "default".to_string(),
+        };
+        store.update_client(client);
+        let first = create_player(&mut store, identity(1), TEAM_ID, at(10)).unwrap();
+        update_client_login_state(&mut store, identity(1), false).unwrap();
+
+        update_client_login_state(&mut store, identity(1), true).unwrap();
+        let second = create_player(&mut store, identity(1), TEAM_ID, at(10)).unwrap();
+
+        assert!(store.client(&identity(1)).unwrap().connected);
+        assert_eq!(store.clients.len(), 1);
+        assert_ne!(first.object_id, second.object_id);
+        assert_eq!(second.appearance.color, "red");
+    }
+
+    #[test]
+    fn removing_missing_player_errors() {
+        let mut store = connected_store(identity(1));
+
+        let result = remove_player(&mut store, identity(1));
+
+        assert_eq!(result.err(), Some(GameError::NotFound("Player")));
+    }
+
+    #[test]
+    fn removing_player_twice_errors() {
+        let mut store = connected_store(identity(1));
+        create_player(&mut store, identity(1), TEAM_ID, at(10)).unwrap();
+        remove_player(&mut store, identity(1)).unwrap();
+
+        assert!(remove_player(&mut store, identity(1)).is_err());
+    }
+
+    #[test]
+    fn disconnect_without_player_is_fine() {
+        let mut store = connected_store(identity(1));
+
+        let removed = update_client_login_state(&mut store, identity(1), false).unwrap();
+
+        assert!(removed.is_none());
+        assert!(!store.client(&identity(1)).unwrap().connected);
+    }
+
+    #[test]
+    fn disconnect_without_client_is_fine() {
+        let mut store = MemoryStore::default();
+
+        let removed = update_client_login_state(&mut store, identity(1), false).unwrap();
+
+        assert!(removed.is_none());
+        assert!(!store.client(&identity(1)).unwrap().connected);
+    }
+
+    #[test]
+    fn disconnect_after_kick_is_fine() {
+        let mut store = connected_store(identity(1));
+        create_player(&mut store, identity(1), TEAM_ID, at(10)).unwrap();
+        remove_player(&mut store, identity(1)).unwrap();
+
+        let removed = update_client_login_state(&mut store, identity(1), false).unwrap();
+
+        assert!(removed.is_none());
+    }
+
+    #[test]
+    fn kicked_clients_wait_to_rejoin() {
+        let mut store = connected_store(identity(1));
+        create_player(&mut store, identity(1), TEAM_ID, at(10)).unwrap();
+        remove_player(&mut store, identity(1)).unwrap();
+
+        block_rejoin(&mut store, identity(1), at(70));
+
+        let result = create_player(&mut store, identity(1), TEAM_ID, at(20));
+        assert_eq!(result.err(), Some(GameError::Kicked));
+        assert!(store.players.is_empty());
+        assert!(create_player(&mut store, identity(1), TEAM_ID, at(70)).is_ok());
+    }
+
+    #[test]
+    fn cleanup_removes_player_without_object() {
+        let mut store = connected_store(identity(1));
+        let player = create_player(&mut store, identity(1), TEAM_ID, at(10)).unwrap();
+        store.delete_object(player.object_id);
+
+        let removed = cleanup_player(&mut store, identity(1));
+
+        assert_eq!(removed.map(|p| p.object_id), Some(player.object_id));
+        assert!(store.players.is_empty());
+    }
+}
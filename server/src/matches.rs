@@ -0,0 +1,320 @@
+use log::info;
+use spacetimedb::{spacetimedb, Identity, ReducerContext, SpacetimeType, Timestamp};
+
+use crate::{
+    error::GameError,
+    items::{self, StdbItem, StdbItemDrop},
+    rooms::{self, StdbRoom},
+    world, StdbObject, StdbPlayer, OBJECT_KIND_ITEM,
+};
+
+pub const DEFAULT_MATCH_DURATION_SECS: u32 = 180;
+pub const DEFAULT_MIN_PLAYERS: u32 = 2;
+pub const MAX_MATCH_DURATION_SECS: u32 = 3600;
+/// How long the countdown runs before a match starts.
+pub const COUNTDOWN_SECS: u32 = 10;
+/// How long the results are shown before the room goes back to waiting.
+pub const RESULTS_SECS: u32 = 15;
+/// How many coins are scattered around the room when a match starts.
+pub const MATCH_COINS: u64 = 20;
+/// The item players collect to score points.
+pub const MATCH_ITEM: &str = "Coin";
+
+#[derive(SpacetimeType, Clone, PartialEq)]
+pub enum StdbMatchState {
+    Waiting,
+    Countdown,
+    Running,
+    Finished,
+}
+
+/// The match being played in a room, advanced by the server every `tick`.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbMatch {
+    #[primarykey]
+    pub room_id: u64,
+    pub state: StdbMatchState,
+    pub state_started_at: Timestamp,
+    // Whole seconds left in the current state, zero while waiting.
+    pub seconds_remaining: u32,
+    pub duration_secs: u32,
+    pub min_players: u32,
+    // Counts up every time a match starts, results are grouped by it.
+    pub match_number: u32,
+}
+
+/// A player's score in the match that's currently running in their room.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbMatchScore {
+    #[primarykey]
+    pub client_id: Identity,
+    pub room_id: u64,
+    pub score: u32,
+}
+
+/// A player's final standing in a finished match.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbMatchResult {
+    #[primarykey]
+    #[autoinc]
+    pub result_id: u64,
+    pub room_id: u64,
+    pub match_number: u32,
+    pub client_id: Identity,
+    pub score: u32,
+    pub rank: u32,
+    pub finished_at: Timestamp,
+}
+
+// Called by the room's owner to change the next match, only while it's waiting for players.
+#[spacetimedb(reducer)]
+pub fn configure_match(
+    ctx: ReducerContext,
+    room_id: u64,
+    duration_secs: u32,
+    min_players: u32,
+) -> Result<(), String> {
+    let room = StdbRoom::filter_by_room_id(&room_id).ok_or(GameError::NotFound("Room"))?;
+    if room.owner_id != ctx.sender {
+        return Err(GameError::NotAllowed("Only the room's owner can configure its match").into());
+    }
+    if duration_secs == 0 || duration_secs > MAX_MATCH_DURATION_SECS {
+        return Err(GameError::Invalid(format!(
+            "Duration must be between 1 and {} seconds",
+            MAX_MATCH_DURATION_SECS
+        ))
+        .into());
+    }
+    if min_players == 0 || min_players > room.capacity {
+        return Err(GameError::Invalid(
+            "Minimum players must be between 1 and the room's capacity".to_string(),
+        )
+        .into());
+    }
+
+    let mut stdb_match =
+        StdbMatch::filter_by_room_id(&room_id).ok_or(GameError::NotFound("Match"))?;
+    if stdb_match.state != StdbMatchState::Waiting {
+        return Err(GameError::Invalid("The match has already started".to_string()).into());
+    }
+    stdb_match.duration_secs = duration_secs;
+    stdb_match.min_players = min_players;
+    StdbMatch::update_by_room_id(&room_id, stdb_match);
+
+    info!("Match configured in room {}", room_id);
+    Ok(())
+}
+
+// Creates the match for a newly created room.
+pub fn open_match(room_id: u64, now: Timestamp, capacity: u32) {
+    StdbMatch::insert(StdbMatch {
+        room_id,
+        state: StdbMatchState::Waiting,
+        state_started_at: now,
+        seconds_remaining: 0,
+        duration_secs: DEFAULT_MATCH_DURATION_SECS,
+        min_players: DEFAULT_MIN_PLAYERS.min(capacity),
+        match_number: 0,
+    })
+    .expect("Failed to insert Match.");
+}
+
+// Removes the match, its scores and its leftover items once the room is closed.
+pub fn close_match(room_id: u64) {
+    StdbMatch::delete_by_room_id(&room_id);
+    clear_scores(room_id);
+    clear_items(room_id);
+}
+
+// Whether the match in `room_id` is counting down or running. The lobby never has a match.
+pub fn is_in_progress(room_id: u64) -> bool {
+    StdbMatch::filter_by_room_id(&room_id).is_some_and(|stdb_match| {
+        matches!(
+            stdb_match.state,
+            StdbMatchState::Countdown | StdbMatchState::Running
+        )
+    })
+}
+
+// Adds `points` to the player's score if a match is running in their room.
+pub fn add_score(client_id: Identity, room_id: u64, points: u32) {
+    let running = StdbMatch::filter_by_room_id(&room_id)
+        .is_some_and(|stdb_match| stdb_match.state == StdbMatchState::Running);
+    if !running {
+        return;
+    }
+
+    match StdbMatchScore::filter_by_client_id(&client_id) {
+        Some(mut score) if score.room_id == room_id => {
+            score.score += points;
+            StdbMatchScore::update_by_client_id(&client_id, score);
+        }
+        Some(_) => {
+            // Their score is from a match in a room they've since left.
+            StdbMatchScore::update_by_client_id(
+                &client_id,
+                StdbMatchScore {
+                    client_id,
+                    room_id,
+                    score: points,
+                },
+            );
+        }
+        None => {
+            StdbMatchScore::insert(StdbMatchScore {
+                client_id,
+                room_id,
+                score: points,
+            })
+            .expect("Failed to insert Match Score.");
+        }
+    }
+}
+
+// Moves every match on to its next state once its time is up, called from `tick`.
+pub fn advance_matches(now: Timestamp) {
+    for mut stdb_match in StdbMatch::iter().collect::<Vec<StdbMatch>>() {
+        let elapsed_secs = secs_since(stdb_match.state_started_at, now);
+        let players = rooms::players_in_room(stdb_match.room_id) as u32;
+        let previous_remaining = stdb_match.seconds_remaining;
+        let room_id = stdb_match.room_id;
+
+        match stdb_match.state {
+            StdbMatchState::Waiting => {
+                if players >= stdb_match.min_players {
+                    set_state(&mut stdb_match, StdbMatchState::Countdown, now);
+                }
+            }
+            StdbMatchState::Countdown => {
+                if players < stdb_match.min_players {
+                    set_state(&mut stdb_match, StdbMatchState::Waiting, now);
+                } else if elapsed_secs >= COUNTDOWN_SECS {
+                    stdb_match.match_number += 1;
+                    start_match(room_id, now);
+                    set_state(&mut stdb_match, StdbMatchState::Running, now);
+                }
+            }
+            StdbMatchState::Running => {
+                if elapsed_secs >= stdb_match.duration_secs {
+                    finish_match(room_id, stdb_match.match_number, now);
+                    set_state(&mut stdb_match, StdbMatchState::Finished, now);
+                }
+            }
+            StdbMatchState::Finished => {
+                if elapsed_secs >= RESULTS_SECS {
+                    set_state(&mut stdb_match, StdbMatchState::Waiting, now);
+                }
+            }
+        }
+
+        let state_secs = match stdb_match.state {
+            StdbMatchState::Waiting => 0,
+            StdbMatchState::Countdown => COUNTDOWN_SECS,
+            StdbMatchState::Running => stdb_match.duration_secs,
+            StdbMatchState::Finished => RESULTS_SECS,
+        };
+        stdb_match.seconds_remaining =
+            state_secs.saturating_sub(secs_since(stdb_match.state_started_at, now));
+
+        // Only write the row when something changed, `tick` runs far more often than once a second.
+        let changed = stdb_match.state_started_at == now
+            || stdb_match.seconds_remaining != previous_remaining;
+        if changed {
+            StdbMatch::update_by_room_id(&room_id, stdb_match);
+        }
+    }
+}
+
+fn secs_since(since: Timestamp, now: Timestamp) -> u32 {
+    now.duration_since(since)
+        .map_or(0, |elapsed| elapsed.as_secs() as u32)
+}
+
+fn set_state(stdb_match: &mut StdbMatch, state: StdbMatchState, now: Timestamp) {
+    info!(
+        "Match {} in room {} is now {}",
+        stdb_match.match_number,
+        stdb_match.room_id,
+        match state {
+            StdbMatchState::Waiting => "waiting",
+            StdbMatchState::Countdown => "counting down",
+            StdbMatchState::Running => "running",
+            StdbMatchState::Finished => "finished",
+        }
+    );
+    stdb_match.state = state;
+    stdb_match.state_started_at = now;
+}
+
+// Resets everyone's score and scatters fresh coins around the room.
+fn start_match(room_id: u64, now: Timestamp) {
+    clear_scores(room_id);
+    clear_items(room_id);
+
+    for player in StdbPlayer::filter_by_room_id(&room_id) {
+        StdbMatchScore::delete_by_client_id(&player.client_id);
+        StdbMatchScore::insert(StdbMatchScore {
+            client_id: player.client_id,
+            room_id,
+            score: 0,
+        })
+        .expect("Failed to insert Match Score.");
+    }
+
+    if let Some(item) = StdbItem::filter_by_name(&MATCH_ITEM.to_string()) {
+        let seed = now.into_micros_since_epoch() ^ room_id;
+        for i in 0..MATCH_COINS {
+            let position = world::scatter_position(seed.wrapping_add(i));
+            items::spawn_item_drop(&item, 1, position, room_id);
+        }
+    }
+}
+
+// Ranks everyone who scored and writes their results.
+fn finish_match(room_id: u64, match_number: u32, now: Timestamp) {
+    let mut scores: Vec<StdbMatchScore> = StdbMatchScore::iter()
+        .filter(|score| score.room_id == room_id)
+        .collect();
+    scores.sort_by(|a, b| b.score.cmp(&a.score));
+
+    for (i, score) in scores.iter().enumerate() {
+        StdbMatchResult::insert(StdbMatchResult {
+            result_id: 0,
+            room_id,
+            match_number,
+            client_id: score.client_id,
+            score: score.score,
+            rank: i as u32 + 1,
+            finished_at: now,
+        })
+        .expect("Failed to insert Match Result.");
+    }
+
+    clear_scores(room_id);
+    clear_items(room_id);
+}
+
+fn clear_scores(room_id: u64) {
+    let client_ids: Vec<Identity> = StdbMatchScore::iter()
+        .filter(|score| score.room_id == room_id)
+        .map(|score| score.client_id)
+        .collect();
+    for client_id in client_ids {
+        StdbMatchScore::delete_by_client_id(&client_id);
+    }
+}
+
+// Deletes every item drop lying in the room.
+fn clear_items(room_id: u64) {
+    let object_ids: Vec<u64> = StdbObject::filter_by_room_id(&room_id)
+        .filter(|object| object.kind == OBJECT_KIND_ITEM)
+        .map(|object| object.object_id)
+        .collect();
+    for object_id in object_ids {
+        StdbItemDrop::delete_by_object_id(&object_id);
+        StdbObject::delete_by_object_id(&object_id);
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use spacetimedb::Identity;
+
+use crate::{error::GameError, store::Store, StdbClient, StdbObject, StdbPlayer};
+
+/// A `Store` kept in memory, for testing the rules in `lifecycle` outside of wasm.
+#[derive(Default)]
+pub struct MemoryStore {
+    pub clients: HashMap<Identity, StdbClient>,
+    pub players: HashMap<Identity, StdbPlayer>,
+    pub objects: HashMap<u64, StdbObject>,
+    // Last `object_id` given out, like an `#[autoinc]` column.
+    last_object_id: u64,
+}
+
+impl Store for MemoryStore {
+    fn client(&self, client_id: &Identity) -> Option<StdbClient> {
+        self.clients.get(client_id).cloned()
+    }
+
+    fn insert_client(&mut self, client: StdbClient) -> Result<(), GameError> {
+        if self.clients.contains_key(&client.client_id) {
+            return Err(GameError::AlreadyExists("Client"));
+        }
+        self.clients.insert(client.client_id, client);
+        Ok(())
+    }
+
+    fn update_client(&mut self, client: StdbClient) {
+        self.clients.insert(client.client_id, client);
+    }
+
+    fn player(&self, client_id: &Identity) -> Option<StdbPlayer> {
+        self.players.get(client_id).cloned()
+    }
+
+    fn insert_player(&mut self, player: StdbPlayer) -> Result<(), GameError> {
+        let taken = self.players.contains_key(&player.client_id)
+            || self
+                .players
+                .values()
+                .any(|other| other.object_id == player.object_id);
+        if taken {
+            return Err(GameError::AlreadyExists("Player"));
+        }
+        self.players.insert(player.client_id, player);
+        Ok(())
+    }
+
+    fn delete_player(&mut self, client_id: &Identity) -> bool {
+        self.players.remove(client_id).is_some()
+    }
+
+    fn object(&self, object_id: u64) -> Option<StdbObject> {
+        self.objects.get(&object_id).cloned()
+    }
+
+    fn insert_object(&mut self, mut object: StdbObject) -> Result<u64, GameError> {
+        self.last_object_id += 1;
+        object.object_id = self.last_object_id;
+        self.objects.insert(object.object_id, object);
+        Ok(self.last_object_id)
+    }
+
+    fn delete_object(&mut self, object_id: u64) -> bool {
+        self.objects.remove(&object_id).is_some()
+    }
+}
@@ -0,0 +1,488 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use log::info;
+use spacetimedb::{spacetimedb, ReducerContext, Timestamp};
+
+use crate::{
+    error::GameError,
+    portals, stats,
+    world::{self, StdbWorld, OBJECT_KIND_ROCK, OBJECT_KIND_TREE, WORLD_ID},
+    zones, StdbObject, StdbPlayer, StdbVector2, OBJECT_KIND_PLAYER,
+};
+
+/// Width and height of a cell of the walkability grid.
+pub const NAV_CELL_SIZE: f32 = 25.0;
+/// How fast a player walks to where they clicked, in units per second.
+pub const PLAYER_PATH_SPEED: f32 = 400.0;
+/// The longest step along a path in one `tick`, so a late tick doesn't teleport anything.
+pub const MAX_STEP_SECS: f32 = 0.5;
+
+// Costs of a step between cells, roughly 1 and the square root of 2 in whole numbers so
+// the search can use an integer heap.
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+/// Which cells of a room can be walked through, rebuilt by `rebuild_grid` from the solid
+/// objects in the room whenever they change.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbNavGrid {
+    #[primarykey]
+    pub room_id: u64,
+    // The corner of the first cell, the grid covers the world bounds.
+    pub min: StdbVector2,
+    pub cell_size: f32,
+    pub width: u32,
+    pub height: u32,
+    // One per cell, row by row, true where a solid object is in the way.
+    pub blocked: Vec<bool>,
+    // Bumped every time the grid is rebuilt, paths found on an older version are found again.
+    pub version: u64,
+}
+
+/// Where an object is being walked to, advanced by the server every `tick`.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbPath {
+    #[primarykey]
+    pub object_id: u64,
+    pub destination: StdbVector2,
+    // Units per second.
+    pub speed: f32,
+    // What's left of the path, the object heads for the first one.
+    pub waypoints: Vec<StdbVector2>,
+    // The `StdbNavGrid::version` the path was found on.
+    pub grid_version: u64,
+}
+
+// The grids loaded during one `tick`, so each is only read once however many objects use it.
+#[derive(Default)]
+pub struct NavGrids(HashMap<u64, StdbNavGrid>);
+
+impl NavGrids {
+    pub fn get(&mut self, room_id: u64) -> &StdbNavGrid {
+        self.0.entry(room_id).or_insert_with(|| grid(room_id))
+    }
+}
+
+// How far around a solid object of `kind` is blocked, `None` for objects that can be
+// walked through.
+pub fn solid_radius(kind: &str) -> Option<f32> {
+    match kind {
+        OBJECT_KIND_ROCK => Some(35.0),
+        OBJECT_KIND_TREE => Some(30.0),
+        _ => None,
+    }
+}
+
+// Called by the client to walk the caller's player to `destination` around anything solid.
+// Moving the player with `update_player_pos` cancels the walk.
+#[spacetimedb(reducer)]
+pub fn move_to(ctx: ReducerContext, destination: StdbVector2) -> Result<(), String> {
+    let player =
+        StdbPlayer::filter_by_client_id(&ctx.sender).ok_or(GameError::NotFound("Player"))?;
+    let object = StdbObject::filter_by_object_id(&player.object_id)
+        .ok_or(GameError::NotFound("Player object"))?;
+    set_path(
+        &grid(object.room_id),
+        &object,
+        world::clamp_to_bounds(destination),
+        PLAYER_PATH_SPEED,
+    )?;
+    Ok(())
+}
+
+impl StdbNavGrid {
+    // Builds the grid over the world bounds, blocking every cell whose center is within the
+    // radius of one of `solids`.
+    pub fn build(
+        room_id: u64,
+        min: StdbVector2,
+        max: StdbVector2,
+        cell_size: f32,
+        solids: &[(StdbVector2, f32)],
+        version: u64,
+    ) -> Self {
+        let width = ((max.x - min.x) / cell_size).ceil().max(1.0) as u32;
+        let height = ((max.y - min.y) / cell_size).ceil().max(1.0) as u32;
+        let mut grid = StdbNavGrid {
+            room_id,
+            min,
+            cell_size,
+            width,
+            height,
+            blocked: vec![false; (width * height) as usize],
+            version,
+        };
+
+        for y in 0..height {
+            for x in 0..width {
+                let center = grid.center((x, y));
+                grid.blocked[(y * width + x) as usize] = solids
+                    .iter()
+                    .any(|(position, radius)| center.distance(position) <= *radius);
+            }
+        }
+        grid
+    }
+
+    pub fn cell_of(&self, position: &StdbVector2) -> Option<(u32, u32)> {
+        let x = (position.x - self.min.x) / self.cell_size;
+        let y = (position.y - self.min.y) / self.cell_size;
+        if x < 0.0 || y < 0.0 || x > self.width as f32 || y > self.height as f32 {
+            return None;
+        }
+        // The far edge belongs to the last cell, `clamp_to_bounds` puts objects right on it.
+        Some((
+            (x as u32).min(self.width - 1),
+            (y as u32).min(self.height - 1),
+        ))
+    }
+
+    pub fn center(&self, (x, y): (u32, u32)) -> StdbVector2 {
+        StdbVector2 {
+            x: self.min.x + (x as f32 + 0.5) * self.cell_size,
+            y: self.min.y + (y as f32 + 0.5) * self.cell_size,
+        }
+    }
+
+    pub fn is_walkable(&self, position: &StdbVector2) -> bool {
+        self.cell_of(position)
+            .is_some_and(|cell| !self.is_blocked(cell))
+    }
+
+    fn is_blocked(&self, (x, y): (u32, u32)) -> bool {
+        self.blocked[(y * self.width + x) as usize]
+    }
+
+    // Searches the grid with A* and returns the waypoints from `from` to `to`, only keeping
+    // the cells where the path turns. Starting inside a blocked cell is fine, so anything
+    // stuck in a solid object can still walk out of it.
+    pub fn find_path(&self, from: &StdbVector2, to: &StdbVector2) -> Option<Vec<StdbVector2>> {
+        let start = self.cell_of(from)?;
+        let goal = self.cell_of(to)?;
+        if self.is_blocked(goal) {
+            return None;
+        }
+
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<(u32, u32), (u32, u32)> = HashMap::new();
+        let mut costs: HashMap<(u32, u32), u32> = HashMap::from([(start, 0)]);
+        open.push(Reverse((self.estimate(start, goal), start)));
+
+        while let Some(Reverse((_, cell))) = open.pop() {
+            if cell == goal {
+                return Some(self.waypoints(&came_from, goal, to));
+            }
+
+            let cost = costs[&cell];
+            for (next, step_cost) in self.neighbors(cell) {
+                let next_cost = cost + step_cost;
+                if costs.get(&next).is_some_and(|known| *known <= next_cost) {
+                    continue;
+                }
+                costs.insert(next, next_cost);
+                came_from.insert(next, cell);
+                open.push(Reverse((next_cost + self.estimate(next, goal), next)));
+            }
+        }
+        None
+    }
+
+    // The octile distance, never more than the real cost of getting from `cell` to `goal`.
+    fn estimate(&self, (x, y): (u32, u32), (goal_x, goal_y): (u32, u32)) -> u32 {
+        let dx = x.abs_diff(goal_x);
+        let dy = y.abs_diff(goal_y);
+        STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+    }
+
+    // The walkable cells around `cell` and the cost of stepping onto them. Diagonal steps
+    // need both cells beside them free, so paths don't cut the corners of solid objects.
+    fn neighbors(&self, (x, y): (u32, u32)) -> Vec<((u32, u32), u32)> {
+        let free = |dx: i64, dy: i64| -> Option<(u32, u32)> {
+            let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+            if nx < 0 || ny < 0 || nx >= self.width as i64 || ny >= self.height as i64 {
+                return None;
+            }
+            let cell = (nx as u32, ny as u32);
+            (!self.is_blocked(cell)).then_some(cell)
+        };
+
+        let mut neighbors = Vec::with_capacity(8);
+        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            if let Some(cell) = free(dx, dy) {
+                neighbors.push((cell, STRAIGHT_COST));
+            }
+        }
+        for (dx, dy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+            if free(dx, 0).is_some() && free(0, dy).is_some() {
+                if let Some(cell) = free(dx, dy) {
+                    neighbors.push((cell, DIAGONAL_COST));
+                }
+            }
+        }
+        neighbors
+    }
+
+    // Walks `came_from` back from `goal`, keeping the cells where the direction changes and
+    // ending exactly on `to` rather than the center of its cell.
+    fn waypoints(
+        &self,
+        came_from: &HashMap<(u32, u32), (u32, u32)>,
+        goal: (u32, u32),
+        to: &StdbVector2,
+    ) -> Vec<StdbVector2> {
+        let mut cells = vec![goal];
+        while let Some(previous) = came_from.get(cells.last().unwrap()) {
+            cells.push(*previous);
+        }
+        cells.reverse();
+
+        let direction =
+            |a: (u32, u32), b: (u32, u32)| (b.0 as i64 - a.0 as i64, b.1 as i64 - a.1 as i64);
+        let mut waypoints: Vec<StdbVector2> = cells
+            .windows(3)
+            .filter(|w| direction(w[0], w[1]) != direction(w[1], w[2]))
+            .map(|w| self.center(w[1]))
+            .collect();
+        waypoints.push(to.clone());
+        waypoints
+    }
+}
+
+// Rebuilds the walkability grid of `room_id` from the solid objects in it, and returns it.
+// Call it whenever a solid object is placed, moved or removed.
+pub fn rebuild_grid(room_id: u64) -> StdbNavGrid {
+    let (min, max) = match StdbWorld::filter_by_world_id(&WORLD_ID) {
+        Some(world) => (world.min, world.max),
+        None => (StdbVector2::default(), StdbVector2::default()),
+    };
+    let solids: Vec<(StdbVector2, f32)> = StdbObject::filter_by_room_id(&room_id)
+        .filter_map(|object| Some((object.position.clone(), solid_radius(&object.kind)?)))
+        .collect();
+
+    let previous = StdbNavGrid::filter_by_room_id(&room_id);
+    let version = previous.as_ref().map_or(1, |grid| grid.version + 1);
+    let grid = StdbNavGrid::build(room_id, min, max, NAV_CELL_SIZE, &solids, version);
+
+    if previous.is_some() {
+        StdbNavGrid::update_by_room_id(&room_id, grid.clone());
+    } else {
+        StdbNavGrid::insert(grid.clone()).expect("Failed to insert Nav Grid.");
+    }
+    info!(
+        "Rebuilt Nav Grid of room {} with {} solid objects",
+        room_id,
+        solids.len()
+    );
+    grid
+}
+
+// The walkability grid of `room_id`, built the first time it's needed.
+pub fn grid(room_id: u64) -> StdbNavGrid {
+    StdbNavGrid::filter_by_room_id(&room_id).unwrap_or_else(|| rebuild_grid(room_id))
+}
+
+// Forgets the grid of a room that was closed.
+pub fn remove_grid(room_id: u64) {
+    StdbNavGrid::delete_by_room_id(&room_id);
+}
+
+// Finds a path on `grid` for `object` to `destination`, replacing the one it had. `grid` has
+// to be the grid of the object's room.
+pub fn set_path(
+    grid: &StdbNavGrid,
+    object: &StdbObject,
+    destination: StdbVector2,
+    speed: f32,
+) -> Result<(), GameError> {
+    let object_id = object.object_id;
+    let waypoints = grid
+        .find_path(&object.position, &destination)
+        .ok_or(GameError::Invalid(
+            "There's no way to get there".to_string(),
+        ))?;
+
+    let path = StdbPath {
+        object_id,
+        destination,
+        speed,
+        waypoints,
+        grid_version: grid.version,
+    };
+    if StdbPath::filter_by_object_id(&object_id).is_some() {
+        StdbPath::update_by_object_id(&object_id, path);
+    } else {
+        StdbPath::insert(path).expect("Failed to insert Path.");
+    }
+    Ok(())
+}
+
+// Stops the object with `object_id` walking its path, if it has one.
+pub fn cancel_path(object_id: u64) {
+    StdbPath::delete_by_object_id(&object_id);
+}
+
+pub fn has_path(object_id: u64) -> bool {
+    StdbPath::filter_by_object_id(&object_id).is_some()
+}
+
+// Moves every object with a path along it, `prev` being when this last ran. Paths found on
+// a grid that has since been rebuilt are found again from where the object is.
+pub fn advance_paths(now: Timestamp, prev: Timestamp, grids: &mut NavGrids) {
+    let secs = now
+        .duration_since(prev)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f32())
+        .min(MAX_STEP_SECS);
+
+    // Paths are updated and deleted along the way, so they're collected first.
+    for mut path in StdbPath::iter().collect::<Vec<StdbPath>>() {
+        let object_id = path.object_id;
+        let Some(mut object) = StdbObject::filter_by_object_id(&object_id) else {
+            cancel_path(object_id);
+            continue;
+        };
+
+        let grid = grids.get(object.room_id);
+        if path.grid_version != grid.version {
+            match grid.find_path(&object.position, &path.destination) {
+                Some(waypoints) => {
+                    path.waypoints = waypoints;
+                    path.grid_version = grid.version;
+                }
+                None => {
+                    stop(object);
+                    cancel_path(object_id);
+                    continue;
+                }
+            }
+        }
+
+        // Players walk faster or slower in some zones.
+        let player = if object.kind == OBJECT_KIND_PLAYER {
+            StdbPlayer::filter_by_object_id(&object_id)
+        } else {
+            None
+        };
+        let speed_multiplier = player
+            .as_ref()
+            .map_or(1.0, |player| zones::speed_multiplier(player.client_id));
+
+        let mut position = object.position.clone();
+        let mut distance = path.speed * speed_multiplier * secs;
+        while let Some(waypoint) = path.waypoints.first() {
+            let remaining = position.distance(waypoint);
+            if remaining > distance {
+                let t = distance / remaining;
+                position = StdbVector2 {
+                    x: position.x + (waypoint.x - position.x) * t,
+                    y: position.y + (waypoint.y - position.y) * t,
+                };
+                break;
+            }
+            distance -= remaining;
+            position = path.waypoints.remove(0);
+        }
+
+        if let Some(player) = &player {
+            stats::add_distance(player.client_id, object.position.distance(&position));
+        }
+        world::move_object(&mut object, position, now);
+        // Stepping into a portal ends the walk, the rest of the path is on the other side.
+        let mut jumped = false;
+        if let Some(player) = &player {
+            jumped = portals::enter_portal(player.client_id, &mut object, now);
+            zones::refresh_occupancy(player.client_id, &object, now);
+        }
+
+        if jumped || path.waypoints.is_empty() {
+            stop(object);
+            cancel_path(object_id);
+        } else {
+            StdbObject::update_by_object_id(&object_id, object);
+            StdbPath::update_by_object_id(&object_id, path);
+        }
+    }
+}
+
+// Saves `object` standing still, so clients don't keep it moving past where it stopped.
+fn stop(mut object: StdbObject) {
+    let object_id = object.object_id;
+    object.velocity = StdbVector2::default();
+    StdbObject::update_by_object_id(&object_id, object);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32, y: f32) -> StdbVector2 {
+        StdbVector2 { x, y }
+    }
+
+    // A 10 by 10 grid of unit cells.
+    fn grid(solids: &[(StdbVector2, f32)]) -> StdbNavGrid {
+        StdbNavGrid::build(0, at(0.0, 0.0), at(10.0, 10.0), 1.0, solids, 1)
+    }
+
+    fn positions(waypoints: &[StdbVector2]) -> Vec<(f32, f32)> {
+        waypoints.iter().map(|w| (w.x, w.y)).collect()
+    }
+
+    #[test]
+    fn straight_paths_have_no_waypoints_in_between() {
+        let path = grid(&[]).find_path(&at(0.5, 0.5), &at(8.2, 0.5)).unwrap();
+
+        assert_eq!(positions(&path), vec![(8.2, 0.5)]);
+    }
+
+    #[test]
+    fn paths_go_around_solid_objects() {
+        // A wall across the middle with a gap at the top.
+        let wall: Vec<(StdbVector2, f32)> =
+            (0..9).map(|y| (at(5.5, y as f32 + 0.5), 0.4)).collect();
+        let grid = grid(&wall);
+        let path = grid.find_path(&at(1.5, 0.5), &at(8.5, 0.5)).unwrap();
+
+        assert!(path.iter().any(|waypoint| waypoint.y >= 9.0));
+        assert!(path.iter().all(|waypoint| grid.is_walkable(waypoint)));
+        assert_eq!(positions(&path).last(), Some(&(8.5, 0.5)));
+    }
+
+    #[test]
+    fn no_path_into_or_past_a_solid_object() {
+        let wall: Vec<(StdbVector2, f32)> =
+            (0..10).map(|y| (at(5.5, y as f32 + 0.5), 0.4)).collect();
+        let grid = grid(&wall);
+
+        assert!(grid.find_path(&at(1.5, 0.5), &at(5.5, 0.5)).is_none());
+        assert!(grid.find_path(&at(1.5, 0.5), &at(8.5, 0.5)).is_none());
+        assert!(grid.find_path(&at(1.5, 0.5), &at(20.0, 0.5)).is_none());
+    }
+
+    #[test]
+    fn paths_can_start_inside_a_solid_object() {
+        let grid = grid(&[(at(0.5, 0.5), 0.4)]);
+
+        assert!(grid.find_path(&at(0.5, 0.5), &at(3.5, 0.5)).is_some());
+    }
+
+    #[test]
+    fn the_far_edge_is_in_the_last_cell() {
+        let grid = grid(&[]);
+
+        assert_eq!(grid.cell_of(&at(10.0, 10.0)), Some((9, 9)));
+        assert!(grid.find_path(&at(0.5, 0.5), &at(10.0, 10.0)).is_some());
+        assert!(grid.cell_of(&at(10.5, 5.0)).is_none());
+    }
+
+    #[test]
+    fn diagonal_steps_dont_cut_corners() {
+        let grid = grid(&[(at(1.5, 0.5), 0.4), (at(0.5, 1.5), 0.4)]);
+
+        assert!(grid.find_path(&at(0.5, 0.5), &at(1.5, 1.5)).is_none());
+    }
+}
@@ -0,0 +1,337 @@
+use log::info;
+use spacetimedb::{spacetimedb, SpacetimeType, Timestamp};
+
+use crate::{
+    navigation::{self, NavGrids},
+    rooms, world, world_time, StdbObject, StdbVector2, OBJECT_KIND_PLAYER,
+};
+
+/// How close a following NPC keeps to the player it follows.
+pub const FOLLOW_DISTANCE: f32 = 60.0;
+
+#[derive(SpacetimeType, Clone, Copy, PartialEq, Debug)]
+pub enum StdbNpcBehavior {
+    // Stands still.
+    Idle,
+    // Walks to random spots within `radius` of its home.
+    Wander,
+    // Walks up to the nearest player within `radius`.
+    Follow,
+    // Runs from the nearest player within `radius`.
+    Flee,
+}
+
+/// Definition of a kind of NPC, every NPC of a type behaves the same way.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbNpcType {
+    #[primarykey]
+    #[autoinc]
+    pub npc_type_id: u64,
+    // Also the `StdbObject::kind` of its NPCs, telling clients how to draw them.
+    #[unique]
+    pub name: String,
+    pub behavior: StdbNpcBehavior,
+    // Units per second.
+    pub speed: f32,
+    // How far from home a wandering NPC goes, or how far away the others notice players.
+    pub radius: f32,
+}
+
+/// An NPC in the world, it shares its `object_id` with the `StdbObject` that positions it.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbNpc {
+    #[primarykey]
+    pub object_id: u64,
+    pub npc_type_id: u64,
+    // Where the NPC was spawned, wandering NPCs stay around it.
+    pub home: StdbVector2,
+    // Where a wandering NPC is walking to.
+    pub target: StdbVector2,
+}
+
+// Inserts the NPC types and a few NPCs of each into the lobby.
+pub fn seed_npcs() {
+    let types = [
+        ("slime", StdbNpcBehavior::Wander, 40.0, 150.0),
+        ("dog", StdbNpcBehavior::Follow, 120.0, 300.0),
+        ("rabbit", StdbNpcBehavior::Flee, 150.0, 200.0),
+        ("guard", StdbNpcBehavior::Idle, 0.0, 0.0),
+    ];
+    for (name, behavior, speed, radius) in types {
+        StdbNpcType::insert(StdbNpcType {
+            npc_type_id: 0,
+            name: name.to_string(),
+            behavior,
+            speed,
+            radius,
+        })
+        .expect("Failed to insert Npc Type.");
+    }
+
+    let npcs = [
+        (
+            "slime",
+            StdbVector2 {
+                x: -350.0,
+                y: 250.0,
+            },
+        ),
+        ("slime", StdbVector2 { x: 400.0, y: 300.0 }),
+        (
+            "dog",
+            StdbVector2 {
+                x: 250.0,
+                y: -300.0,
+            },
+        ),
+        (
+            "rabbit",
+            StdbVector2 {
+                x: -450.0,
+                y: -200.0,
+            },
+        ),
+        ("rabbit", StdbVector2 { x: 550.0, y: 50.0 }),
+        ("guard", StdbVector2 { x: 0.0, y: 150.0 }),
+    ];
+    for (name, position) in npcs {
+        if let Some(npc_type) = StdbNpcType::filter_by_name(&name.to_string()) {
+            spawn_npc(&npc_type, position, rooms::LOBBY_ROOM_ID);
+        }
+    }
+    info!("Seeded Npcs");
+}
+
+// Creates the `StdbObject` and `StdbNpc` rows for an NPC of `npc_type` at home at `position`.
+pub fn spawn_npc(npc_type: &StdbNpcType, position: StdbVector2, room_id: u64) -> u64 {
+    let object_id = StdbObject::insert(StdbObject {
+        object_id: 0,
+        name: npc_type.name.clone(),
+        kind: npc_type.name.clone(),
+        room_id,
+        position: position.clone(),
+        ..Default::default()
+    })
+    .expect("Failed to create a unique Npc.")
+    .object_id;
+
+    StdbNpc::insert(StdbNpc {
+        object_id,
+        npc_type_id: npc_type.npc_type_id,
+        home: position.clone(),
+        target: position,
+    })
+    .expect("Failed to insert Npc.");
+
+    object_id
+}
+
+// Moves every NPC one step along its behavior, `prev` being when this last ran. Wandering
+// NPCs are given a path instead, `navigation::advance_paths` walks them along it, and head
+// home at night.
+pub fn advance_npcs(now: Timestamp, prev: Timestamp, grids: &mut NavGrids) {
+    let secs = now
+        .duration_since(prev)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f32())
+        .min(navigation::MAX_STEP_SECS);
+
+    // Every NPC looks for players, so they're only collected once.
+    let players: Vec<(u64, StdbVector2)> = StdbObject::iter()
+        .filter(|object| object.kind == OBJECT_KIND_PLAYER)
+        .map(|object| (object.room_id, object.position))
+        .collect();
+    let night = world_time::is_night_at(now);
+
+    // NPCs are updated along the way, so they're collected first.
+    for mut npc in StdbNpc::iter().collect::<Vec<StdbNpc>>() {
+        let (Some(npc_type), Some(mut object)) = (
+            StdbNpcType::filter_by_npc_type_id(&npc.npc_type_id),
+            StdbObject::filter_by_object_id(&npc.object_id),
+        ) else {
+            continue;
+        };
+
+        if npc_type.behavior == StdbNpcBehavior::Wander {
+            if !navigation::has_path(npc.object_id) {
+                // Once home for the night they rest until morning.
+                if night && object.position.distance(&npc.home) < 1.0 {
+                    continue;
+                }
+                npc.target = if night {
+                    npc.home.clone()
+                } else {
+                    wander_target(&npc, &npc_type, now)
+                };
+                // Targets inside something solid are skipped, another is picked next tick.
+                let grid = grids.get(object.room_id);
+                if navigation::set_path(grid, &object, npc.target.clone(), npc_type.speed).is_ok() {
+                    StdbNpc::update_by_object_id(&npc.object_id, npc.clone());
+                }
+            }
+            continue;
+        }
+
+        let nearest_player = players
+            .iter()
+            .filter(|(room_id, _)| *room_id == object.room_id)
+            .map(|(_, position)| position)
+            .filter(|position| object.position.distance(position) <= npc_type.radius)
+            .min_by(|a, b| {
+                object
+                    .position
+                    .distance(a)
+                    .total_cmp(&object.position.distance(b))
+            });
+
+        let heading = destination(&npc_type, &object.position, nearest_player);
+        let position = match heading {
+            Some(heading) => world::clamp_to_bounds(step_towards(
+                &object.position,
+                &heading,
+                npc_type.speed * secs,
+            )),
+            None => object.position.clone(),
+        };
+        // Steering NPCs stop at anything solid rather than walk through it.
+        let position = if grids.get(object.room_id).is_walkable(&position) {
+            position
+        } else {
+            object.position.clone()
+        };
+
+        // Standing NPCs are only updated once, to tell clients they stopped.
+        let moved = position.x != object.position.x || position.y != object.position.y;
+        let was_moving = object.velocity.x != 0.0 || object.velocity.y != 0.0;
+        if moved || was_moving {
+            world::move_object(&mut object, position, now);
+            StdbObject::update_by_object_id(&npc.object_id, object);
+        }
+    }
+}
+
+// Where an NPC of `npc_type` at `position` is heading, `None` when it stays put or is
+// walked along a path.
+fn destination(
+    npc_type: &StdbNpcType,
+    position: &StdbVector2,
+    nearest_player: Option<&StdbVector2>,
+) -> Option<StdbVector2> {
+    match npc_type.behavior {
+        StdbNpcBehavior::Idle | StdbNpcBehavior::Wander => None,
+        StdbNpcBehavior::Follow => {
+            let player = nearest_player?;
+            if position.distance(player) <= FOLLOW_DISTANCE {
+                return None;
+            }
+            Some(step_towards(player, position, FOLLOW_DISTANCE))
+        }
+        StdbNpcBehavior::Flee => {
+            let player = nearest_player?;
+            Some(step_towards(position, player, -npc_type.radius))
+        }
+    }
+}
+
+// Returns `from` moved `distance` towards `to` without passing it, a negative `distance`
+// moves away from `to` instead.
+fn step_towards(from: &StdbVector2, to: &StdbVector2, distance: f32) -> StdbVector2 {
+    let remaining = from.distance(to);
+    if remaining == 0.0 {
+        return from.clone();
+    }
+
+    let t = (distance / remaining).min(1.0);
+    StdbVector2 {
+        x: from.x + (to.x - from.x) * t,
+        y: from.y + (to.y - from.y) * t,
+    }
+}
+
+// Picks a spot within `radius` of the NPC's home, different for every NPC and every time.
+fn wander_target(npc: &StdbNpc, npc_type: &StdbNpcType, now: Timestamp) -> StdbVector2 {
+    let z = world::scramble(now.into_micros_since_epoch() ^ npc.object_id);
+    let angle = (z & 0xFFFF_FFFF) as f32 / u32::MAX as f32 * std::f32::consts::TAU;
+    // The square root spreads targets evenly over the circle instead of bunching them at home.
+    let distance = ((z >> 32) as f32 / u32::MAX as f32).sqrt() * npc_type.radius;
+    StdbVector2 {
+        x: npc.home.x + angle.cos() * distance,
+        y: npc.home.y + angle.sin() * distance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn npc_type(behavior: StdbNpcBehavior) -> StdbNpcType {
+        StdbNpcType {
+            npc_type_id: 1,
+            name: "npc".to_string(),
+            behavior,
+            speed: 100.0,
+            radius: 200.0,
+        }
+    }
+
+    fn npc(home: StdbVector2) -> StdbNpc {
+        StdbNpc {
+            object_id: 1,
+            npc_type_id: 1,
+            home: home.clone(),
+            target: home,
+        }
+    }
+
+    fn at(x: f32, y: f32) -> StdbVector2 {
+        StdbVector2 { x, y }
+    }
+
+    #[test]
+    fn steps_never_pass_their_target() {
+        let step = step_towards(&at(0.0, 0.0), &at(10.0, 0.0), 25.0);
+        assert_eq!((step.x, step.y), (10.0, 0.0));
+
+        let step = step_towards(&at(0.0, 0.0), &at(10.0, 0.0), 4.0);
+        assert_eq!((step.x, step.y), (4.0, 0.0));
+    }
+
+    #[test]
+    fn idle_npcs_stay_put() {
+        let npc_type = npc_type(StdbNpcBehavior::Idle);
+        let player = at(50.0, 0.0);
+
+        assert!(destination(&npc_type, &at(0.0, 0.0), Some(&player)).is_none());
+    }
+
+    #[test]
+    fn wandering_npcs_stay_near_home() {
+        let npc_type = npc_type(StdbNpcBehavior::Wander);
+        let npc = npc(at(100.0, -50.0));
+
+        for micros in 0..100 {
+            let target = wander_target(&npc, &npc_type, Timestamp::from_micros_since_epoch(micros));
+            assert!(target.distance(&npc.home) <= npc_type.radius + 0.01);
+        }
+        assert!(destination(&npc_type, &at(0.0, 0.0), None).is_none());
+    }
+
+    #[test]
+    fn following_npcs_stop_short_of_the_player() {
+        let npc_type = npc_type(StdbNpcBehavior::Follow);
+        let heading = destination(&npc_type, &at(0.0, 0.0), Some(&at(100.0, 0.0))).unwrap();
+        assert_eq!((heading.x, heading.y), (100.0 - FOLLOW_DISTANCE, 0.0));
+        assert!(destination(&npc_type, &at(0.0, 0.0), Some(&at(30.0, 0.0))).is_none());
+        assert!(destination(&npc_type, &at(0.0, 0.0), None).is_none());
+    }
+
+    #[test]
+    fn fleeing_npcs_run_away_from_the_player() {
+        let npc_type = npc_type(StdbNpcBehavior::Flee);
+        let heading = destination(&npc_type, &at(0.0, 0.0), Some(&at(50.0, 0.0))).unwrap();
+
+        assert!(heading.x < 0.0);
+        assert_eq!(heading.y, 0.0);
+    }
+}
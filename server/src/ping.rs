@@ -0,0 +1,57 @@
+use log::info;
+use spacetimedb::{spacetimedb, Identity, ReducerContext, Timestamp};
+
+use crate::{error::GameError, world, StdbPlayer, StdbVector2};
+
+/// How long a ping stays in the world before `expire_pings` removes it.
+pub const PING_LIFETIME_MICROS: u64 = 5_000_000;
+
+/// A temporary marker placed by a player, clients show it to players in the same room near `position`.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbPing {
+    #[primarykey]
+    #[autoinc]
+    pub ping_id: u64,
+    #[unique]
+    pub client_id: Identity,
+    pub room_id: u64,
+    pub position: StdbVector2,
+    pub created_at: Timestamp,
+}
+
+// Called by the client when they click on the minimap. Every client only has
+// one ping at a time, so a new ping replaces the previous one.
+#[spacetimedb(reducer)]
+pub fn ping(ctx: ReducerContext, position: StdbVector2) -> Result<(), String> {
+    let player =
+        StdbPlayer::filter_by_client_id(&ctx.sender).ok_or(GameError::NotFound("Player"))?;
+
+    StdbPing::delete_by_client_id(&ctx.sender);
+    StdbPing::insert(StdbPing {
+        ping_id: 0,
+        client_id: ctx.sender,
+        room_id: player.room_id,
+        position: world::clamp_to_bounds(position),
+        created_at: ctx.timestamp,
+    })
+    .expect("Failed to insert Ping.");
+
+    info!("Pinged by {}", ctx.sender);
+    Ok(())
+}
+
+// Removes every ping older than `PING_LIFETIME_MICROS`.
+pub fn expire_pings(now: Timestamp) {
+    let expired: Vec<u64> = StdbPing::iter()
+        .filter(|ping| {
+            now.duration_since(ping.created_at)
+                .map_or(false, |age| age.as_micros() >= PING_LIFETIME_MICROS as u128)
+        })
+        .map(|ping| ping.ping_id)
+        .collect();
+
+    for ping_id in expired {
+        StdbPing::delete_by_ping_id(&ping_id);
+    }
+}
@@ -0,0 +1,215 @@
+use log::info;
+use spacetimedb::{spacetimedb, Identity, Timestamp};
+
+use crate::{rooms, world, StdbObject, StdbVector2};
+
+pub const OBJECT_KIND_PORTAL: &str = "portal";
+
+/// How long after a jump a player can't use another portal, so they aren't sent straight
+/// back by the portal they arrive next to.
+pub const PORTAL_COOLDOWN_MICROS: u64 = 2_000_000;
+
+/// A portal in the world, it shares its `object_id` with the `StdbObject` that positions it.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbPortal {
+    #[primarykey]
+    pub object_id: u64,
+    // Where players stepping into the portal are sent, in the portal's room.
+    pub destination: StdbVector2,
+    // How close to the portal a player has to be to step into it.
+    pub radius: f32,
+}
+
+/// The last portal jump of every player, clients use it to hide the jump.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbPortalJump {
+    #[primarykey]
+    pub client_id: Identity,
+    pub portal_id: u64,
+    pub from: StdbVector2,
+    pub to: StdbVector2,
+    pub jumped_at: Timestamp,
+}
+
+impl StdbPortalJump {
+    pub fn is_cooling_down(&self, now: Timestamp) -> bool {
+        now.duration_since(self.jumped_at)
+            .map_or(true, |age| age.as_micros() < PORTAL_COOLDOWN_MICROS as u128)
+    }
+
+    // Whether a position the client sent is from before it learned of the jump, it's
+    // still nearer where the player jumped from than where they landed.
+    pub fn is_stale(&self, position: &StdbVector2, now: Timestamp) -> bool {
+        self.is_cooling_down(now) && position.distance(&self.from) < position.distance(&self.to)
+    }
+}
+
+// The two linked pairs of portals in the lobby, they have to be inside the world bounds or
+// players could never reach them.
+fn seeded_pairs() -> [(StdbVector2, StdbVector2); 2] {
+    [
+        (
+            StdbVector2 {
+                x: -800.0,
+                y: 400.0,
+            },
+            StdbVector2 {
+                x: 800.0,
+                y: -400.0,
+            },
+        ),
+        (
+            StdbVector2 {
+                x: -700.0,
+                y: -600.0,
+            },
+            StdbVector2 { x: 700.0, y: 600.0 },
+        ),
+    ]
+}
+
+// Inserts the linked pairs of portals into the lobby, each portal drops players next to its
+// partner.
+pub fn seed_portals() {
+    for (a, b) in seeded_pairs() {
+        spawn_portal(a.clone(), beside(&b), rooms::LOBBY_ROOM_ID);
+        spawn_portal(b.clone(), beside(&a), rooms::LOBBY_ROOM_ID);
+    }
+    info!("Seeded Portals");
+}
+
+// Creates the `StdbObject` and `StdbPortal` rows for a portal at `position` leading to
+// `destination`.
+pub fn spawn_portal(position: StdbVector2, destination: StdbVector2, room_id: u64) -> u64 {
+    let object_id = StdbObject::insert(StdbObject {
+        object_id: 0,
+        name: OBJECT_KIND_PORTAL.to_string(),
+        kind: OBJECT_KIND_PORTAL.to_string(),
+        room_id,
+        position,
+        ..Default::default()
+    })
+    .expect("Failed to create a unique Portal.")
+    .object_id;
+
+    StdbPortal::insert(StdbPortal {
+        object_id,
+        destination,
+        radius: 30.0,
+    })
+    .expect("Failed to insert Portal.");
+
+    object_id
+}
+
+// Sends the client's player `object` through the portal it's standing in, if any and it
+// isn't cooling down from the last jump. Returns whether it jumped, the caller saves `object`.
+pub fn enter_portal(client_id: Identity, object: &mut StdbObject, now: Timestamp) -> bool {
+    if last_jump(client_id).is_some_and(|jump| jump.is_cooling_down(now)) {
+        return false;
+    }
+
+    let Some(portal) = StdbPortal::iter().find(|portal| {
+        StdbObject::filter_by_object_id(&portal.object_id).is_some_and(|portal_object| {
+            portal_object.room_id == object.room_id
+                && portal_object.position.distance(&object.position) <= portal.radius
+        })
+    }) else {
+        return false;
+    };
+
+    let jump = StdbPortalJump {
+        client_id,
+        portal_id: portal.object_id,
+        from: object.position.clone(),
+        to: world::clamp_to_bounds(portal.destination),
+        jumped_at: now,
+    };
+    world::place_object(object, jump.to.clone(), now);
+    if StdbPortalJump::filter_by_client_id(&client_id).is_some() {
+        StdbPortalJump::update_by_client_id(&client_id, jump);
+    } else {
+        StdbPortalJump::insert(jump).expect("Failed to insert Portal Jump.");
+    }
+    true
+}
+
+pub fn last_jump(client_id: Identity) -> Option<StdbPortalJump> {
+    StdbPortalJump::filter_by_client_id(&client_id)
+}
+
+// Forgets the client's last jump once their player is gone.
+pub fn forget_client(client_id: Identity) {
+    StdbPortalJump::delete_by_client_id(&client_id);
+}
+
+// Where a portal leading to the one at `position` drops players, far enough away that they
+// don't step straight back into it.
+fn beside(position: &StdbVector2) -> StdbVector2 {
+    StdbVector2 {
+        x: position.x,
+        y: position.y - 70.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32, y: f32) -> StdbVector2 {
+        StdbVector2 { x, y }
+    }
+
+    fn jump(jumped_at_micros: u64) -> StdbPortalJump {
+        StdbPortalJump {
+            client_id: Identity::from_byte_array([1; 32]),
+            portal_id: 1,
+            from: at(0.0, 0.0),
+            to: at(100.0, 0.0),
+            jumped_at: Timestamp::from_micros_since_epoch(jumped_at_micros),
+        }
+    }
+
+    #[test]
+    fn jumps_cool_down() {
+        let jump = jump(1_000_000);
+
+        assert!(jump.is_cooling_down(Timestamp::from_micros_since_epoch(1_500_000)));
+        assert!(!jump.is_cooling_down(Timestamp::from_micros_since_epoch(
+            1_000_000 + PORTAL_COOLDOWN_MICROS
+        )));
+    }
+
+    #[test]
+    fn positions_from_before_the_jump_are_stale() {
+        let jump = jump(1_000_000);
+        let now = Timestamp::from_micros_since_epoch(1_100_000);
+
+        assert!(jump.is_stale(&at(5.0, 0.0), now));
+        assert!(!jump.is_stale(&at(95.0, 0.0), now));
+        // Once the cooldown is over the player may have walked back on their own.
+        let later = Timestamp::from_micros_since_epoch(1_000_000 + PORTAL_COOLDOWN_MICROS);
+        assert!(!jump.is_stale(&at(5.0, 0.0), later));
+    }
+
+    #[test]
+    fn seeded_portals_are_inside_the_world() {
+        let world = world::seeded_world();
+
+        for (a, b) in seeded_pairs() {
+            for position in [beside(&a), a, beside(&b), b] {
+                let clamped = world.clamp(position.clone());
+                assert_eq!((clamped.x, clamped.y), (position.x, position.y));
+            }
+        }
+    }
+
+    #[test]
+    fn portals_drop_players_outside_their_partner() {
+        let partner = at(800.0, -400.0);
+
+        assert!(beside(&partner).distance(&partner) > 30.0);
+    }
+}
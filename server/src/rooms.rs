@@ -0,0 +1,126 @@
+use log::info;
+use spacetimedb::{spacetimedb, Identity, ReducerContext, Timestamp};
+
+use crate::{error::GameError, matches, navigation, teams, zones, StdbObject, StdbPlayer};
+
+/// `room_id` of the shared lobby every player starts in. The lobby has no `StdbRoom` row
+/// and no capacity.
+pub const LOBBY_ROOM_ID: u64 = 0;
+/// The most players a single room can be created for.
+pub const MAX_ROOM_CAPACITY: u32 = 32;
+/// Longest room name, in characters, that `create_room` accepts.
+pub const ROOM_NAME_MAX_LEN: usize = 32;
+
+/// A space separate from the lobby, only objects with the same `room_id` share it.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbRoom {
+    #[primarykey]
+    #[autoinc]
+    pub room_id: u64,
+    pub name: String,
+    pub owner_id: Identity,
+    pub capacity: u32,
+}
+
+// Creates a new room owned by the caller and moves them into it.
+#[spacetimedb(reducer)]
+pub fn create_room(ctx: ReducerContext, name: String, capacity: u32) -> Result<(), String> {
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > ROOM_NAME_MAX_LEN {
+        return Err(GameError::Invalid("Room name is empty or too long".to_string()).into());
+    }
+    if capacity == 0 || capacity > MAX_ROOM_CAPACITY {
+        return Err(GameError::Invalid(format!(
+            "Capacity must be between 1 and {}",
+            MAX_ROOM_CAPACITY
+        ))
+        .into());
+    }
+    let player =
+        StdbPlayer::filter_by_client_id(&ctx.sender).ok_or(GameError::NotFound("Player"))?;
+
+    let room = StdbRoom::insert(StdbRoom {
+        room_id: 0,
+        name,
+        owner_id: ctx.sender,
+        capacity,
+    })
+    .expect("Failed to create a unique Room.");
+    matches::open_match(room.room_id, ctx.timestamp, capacity);
+    info!("Room created: {}", room.room_id);
+
+    move_player_to_room(player, room.room_id, ctx.timestamp);
+    Ok(())
+}
+
+// Moves the caller into the room with `room_id` if it isn't full.
+#[spacetimedb(reducer)]
+pub fn join_room(ctx: ReducerContext, room_id: u64) -> Result<(), String> {
+    let player =
+        StdbPlayer::filter_by_client_id(&ctx.sender).ok_or(GameError::NotFound("Player"))?;
+    if player.room_id == room_id {
+        return Err(GameError::Invalid("Already in that room".to_string()).into());
+    }
+
+    if room_id != LOBBY_ROOM_ID {
+        let room = StdbRoom::filter_by_room_id(&room_id).ok_or(GameError::NotFound("Room"))?;
+        if players_in_room(room_id) >= room.capacity as usize {
+            return Err(GameError::Invalid("Room is full".to_string()).into());
+        }
+    }
+
+    move_player_to_room(player, room_id, ctx.timestamp);
+    Ok(())
+}
+
+// Moves the caller back into the lobby.
+#[spacetimedb(reducer)]
+pub fn leave_room(ctx: ReducerContext) -> Result<(), String> {
+    let player =
+        StdbPlayer::filter_by_client_id(&ctx.sender).ok_or(GameError::NotFound("Player"))?;
+    if player.room_id == LOBBY_ROOM_ID {
+        return Err(GameError::Invalid("Not in a room".to_string()).into());
+    }
+
+    move_player_to_room(player, LOBBY_ROOM_ID, ctx.timestamp);
+    Ok(())
+}
+
+pub fn players_in_room(room_id: u64) -> usize {
+    StdbPlayer::filter_by_room_id(&room_id).count()
+}
+
+// Deletes the room with `room_id` once the last player has left it.
+pub fn close_room_if_empty(room_id: u64) {
+    if room_id == LOBBY_ROOM_ID || players_in_room(room_id) > 0 {
+        return;
+    }
+
+    if StdbRoom::delete_by_room_id(&room_id) {
+        matches::close_match(room_id);
+        navigation::remove_grid(room_id);
+        info!("Room closed: {}", room_id);
+    }
+}
+
+// Moves the player and their object into `room_id`, and onto whichever team there has the
+// fewest players. The zones of the room they left are left too.
+fn move_player_to_room(mut player: StdbPlayer, room_id: u64, now: Timestamp) {
+    let previous_room_id = player.room_id;
+
+    navigation::cancel_path(player.object_id);
+    if let Some(mut object) = StdbObject::filter_by_object_id(&player.object_id) {
+        object.room_id = room_id;
+        zones::refresh_occupancy(player.client_id, &object, now);
+        StdbObject::update_by_object_id(&player.object_id, object);
+    }
+
+    player.room_id = room_id;
+    player.team_id = teams::balanced_team(room_id);
+    let object_id = player.object_id;
+    StdbPlayer::update_by_object_id(&object_id, player);
+
+    close_room_if_empty(previous_room_id);
+    info!("Player moved from room {} to {}", previous_room_id, room_id);
+}
@@ -0,0 +1,129 @@
+use spacetimedb::{spacetimedb, Identity, Timestamp};
+
+use crate::StdbClient;
+
+/// How many of the top players are kept in `StdbLeaderboardEntry`.
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Everything recorded about a client across all of their sessions.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbPlayerStats {
+    #[primarykey]
+    pub client_id: Identity,
+    pub sessions: u32,
+    // Play time of every finished session, the current one is counted from `session_started_at`.
+    pub play_time_micros: u64,
+    pub session_started_at: Timestamp,
+    // Only movement sent through `update_player_pos` counts, teleports and room changes don't.
+    pub distance_traveled: f32,
+}
+
+/// The top `LEADERBOARD_SIZE` clients by play time, rebuilt from `StdbPlayerStats` by `tick`
+/// so clients can subscribe to it instead of sorting every client's stats themselves.
+#[spacetimedb(table)]
+#[derive(Clone, PartialEq)]
+pub struct StdbLeaderboardEntry {
+    #[primarykey]
+    pub rank: u32,
+    pub client_id: Identity,
+    pub play_time_secs: u64,
+    pub distance_traveled: f32,
+    pub sessions: u32,
+}
+
+// Starts a new session for the client, creating their stats on their first connection.
+pub fn start_session(client_id: Identity, now: Timestamp) {
+    match StdbPlayerStats::filter_by_client_id(&client_id) {
+        Some(mut stats) => {
+            stats.sessions += 1;
+            stats.session_started_at = now;
+            StdbPlayerStats::update_by_client_id(&client_id, stats);
+        }
+        None => {
+            StdbPlayerStats::insert(StdbPlayerStats {
+                client_id,
+                sessions: 1,
+                play_time_micros: 0,
+                session_started_at: now,
+                distance_traveled: 0.0,
+            })
+            .expect("Failed to insert Player Stats.");
+        }
+    }
+}
+
+// Adds the length of the client's current session to their play time. Clients that were
+// turned away on connect, e.g. for being banned, never started one.
+pub fn end_session(client_id: Identity, now: Timestamp) {
+    if !is_connected(&client_id) {
+        return;
+    }
+
+    if let Some(mut stats) = StdbPlayerStats::filter_by_client_id(&client_id) {
+        stats.play_time_micros += session_micros(&stats, now);
+        StdbPlayerStats::update_by_client_id(&client_id, stats);
+    }
+}
+
+pub fn add_distance(client_id: Identity, distance: f32) {
+    if distance <= 0.0 {
+        return;
+    }
+
+    if let Some(mut stats) = StdbPlayerStats::filter_by_client_id(&client_id) {
+        stats.distance_traveled += distance;
+        StdbPlayerStats::update_by_client_id(&client_id, stats);
+    }
+}
+
+// Total play time including the session the client is in right now, if they're connected.
+pub fn play_time_secs(stats: &StdbPlayerStats, now: Timestamp) -> u64 {
+    let current_micros = if is_connected(&stats.client_id) {
+        session_micros(stats, now)
+    } else {
+        0
+    };
+
+    (stats.play_time_micros + current_micros) / 1_000_000
+}
+
+// Ranks every client by play time and writes the top `LEADERBOARD_SIZE`, called from `tick`.
+// Rows are only written when they changed, which is at most once a second per entry.
+pub fn refresh_leaderboard(now: Timestamp) {
+    let mut ranked: Vec<(u64, StdbPlayerStats)> = StdbPlayerStats::iter()
+        .map(|stats| (play_time_secs(&stats, now), stats))
+        .collect();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0));
+    ranked.truncate(LEADERBOARD_SIZE);
+
+    for (index, (play_time_secs, stats)) in ranked.into_iter().enumerate() {
+        let rank = index as u32 + 1;
+        let entry = StdbLeaderboardEntry {
+            rank,
+            client_id: stats.client_id,
+            play_time_secs,
+            distance_traveled: stats.distance_traveled,
+            sessions: stats.sessions,
+        };
+
+        match StdbLeaderboardEntry::filter_by_rank(&rank) {
+            Some(existing) if existing == entry => {}
+            Some(_) => {
+                StdbLeaderboardEntry::update_by_rank(&rank, entry);
+            }
+            None => {
+                StdbLeaderboardEntry::insert(entry).expect("Failed to insert Leaderboard Entry.");
+            }
+        }
+    }
+}
+
+fn is_connected(client_id: &Identity) -> bool {
+    StdbClient::filter_by_client_id(client_id).is_some_and(|client| client.connected)
+}
+
+fn session_micros(stats: &StdbPlayerStats, now: Timestamp) -> u64 {
+    now.duration_since(stats.session_started_at)
+        .map_or(0, |elapsed| elapsed.as_micros() as u64)
+}
@@ -0,0 +1,68 @@
+use spacetimedb::Identity;
+
+use crate::{error::GameError, StdbClient, StdbObject, StdbPlayer};
+
+/// The table operations the rules in `lifecycle` need. Reducers pass `StdbStore`, which goes
+/// through the module's tables, while tests pass the in-memory `memory_store::MemoryStore`
+/// so the rules can run natively instead of in wasm.
+pub trait Store {
+    fn client(&self, client_id: &Identity) -> Option<StdbClient>;
+    fn insert_client(&mut self, client: StdbClient) -> Result<(), GameError>;
+    fn update_client(&mut self, client: StdbClient);
+
+    fn player(&self, client_id: &Identity) -> Option<StdbPlayer>;
+    fn insert_player(&mut self, player: StdbPlayer) -> Result<(), GameError>;
+    /// Returns whether there was a player to delete.
+    fn delete_player(&mut self, client_id: &Identity) -> bool;
+
+    fn object(&self, object_id: u64) -> Option<StdbObject>;
+    /// Inserts `object` with a new `object_id` and returns it.
+    fn insert_object(&mut self, object: StdbObject) -> Result<u64, GameError>;
+    /// Returns whether there was an object to delete.
+    fn delete_object(&mut self, object_id: u64) -> bool;
+}
+
+/// The `Store` every reducer uses, backed by the module's tables.
+pub struct StdbStore;
+
+impl Store for StdbStore {
+    fn client(&self, client_id: &Identity) -> Option<StdbClient> {
+        StdbClient::filter_by_client_id(client_id)
+    }
+
+    fn insert_client(&mut self, client: StdbClient) -> Result<(), GameError> {
+        StdbClient::insert(client).map_err(|_| GameError::AlreadyExists("Client"))?;
+        Ok(())
+    }
+
+    fn update_client(&mut self, client: StdbClient) {
+        let client_id = client.client_id;
+        StdbClient::update_by_client_id(&client_id, client);
+    }
+
+    fn player(&self, client_id: &Identity) -> Option<StdbPlayer> {
+        StdbPlayer::filter_by_client_id(client_id)
+    }
+
+    fn insert_player(&mut self, player: StdbPlayer) -> Result<(), GameError> {
+        StdbPlayer::insert(player).map_err(|_| GameError::AlreadyExists("Player"))?;
+        Ok(())
+    }
+
+    fn delete_player(&mut self, client_id: &Identity) -> bool {
+        StdbPlayer::delete_by_client_id(client_id)
+    }
+
+    fn object(&self, object_id: u64) -> Option<StdbObject> {
+        StdbObject::filter_by_object_id(&object_id)
+    }
+
+    fn insert_object(&mut self, object: StdbObject) -> Result<u64, GameError> {
+        let object = StdbObject::insert(object).map_err(|_| GameError::AlreadyExists("Object"))?;
+        Ok(object.object_id)
+    }
+
+    fn delete_object(&mut self, object_id: u64) -> bool {
+        StdbObject::delete_by_object_id(&object_id)
+    }
+}
@@ -0,0 +1,74 @@
+use log::info;
+use spacetimedb::{spacetimedb, ReducerContext};
+
+use crate::{error::GameError, matches, StdbPlayer};
+
+/// `team_id` of chat messages sent to everyone rather than a single team. No team uses it.
+pub const NO_TEAM: u64 = 0;
+
+/// A side players are split into, every player is on exactly one team.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbTeam {
+    #[primarykey]
+    pub team_id: u64,
+    pub name: String,
+    // One of `appearance::ALLOWED_COLORS`, the client tints the team's players with it.
+    pub color: String,
+}
+
+pub fn seed_teams() {
+    for (team_id, name, color) in [(1, "Red", "red"), (2, "Blue", "blue")] {
+        StdbTeam::insert(StdbTeam {
+            team_id,
+            name: name.to_string(),
+            color: color.to_string(),
+        })
+        .expect("Failed to insert Team.");
+    }
+}
+
+// Called by the client to move to another team. Teams can't be switched during a match,
+// and only to a team with fewer players in the room so the teams never get more uneven.
+#[spacetimedb(reducer)]
+pub fn switch_team(ctx: ReducerContext, team_id: u64) -> Result<(), String> {
+    let mut player =
+        StdbPlayer::filter_by_client_id(&ctx.sender).ok_or(GameError::NotFound("Player"))?;
+    if StdbTeam::filter_by_team_id(&team_id).is_none() {
+        return Err(GameError::NotFound("Team").into());
+    }
+    if player.team_id == team_id {
+        return Err(GameError::Invalid("Already on that team".to_string()).into());
+    }
+    if matches::is_in_progress(player.room_id) {
+        return Err(GameError::Invalid("Can't switch teams during a match".to_string()).into());
+    }
+    if players_on_team(player.room_id, team_id) >= players_on_team(player.room_id, player.team_id) {
+        return Err(GameError::Invalid("That team already has enough players".to_string()).into());
+    }
+
+    info!(
+        "Player {} switched from team {} to {}",
+        ctx.sender, player.team_id, team_id
+    );
+    player.team_id = team_id;
+    let object_id = player.object_id;
+    StdbPlayer::update_by_object_id(&object_id, player);
+    Ok(())
+}
+
+// Picks the team with the fewest players in `room_id`, the first team wins ties.
+pub fn balanced_team(room_id: u64) -> u64 {
+    let mut team_ids: Vec<u64> = StdbTeam::iter().map(|team| team.team_id).collect();
+    team_ids.sort_unstable();
+    team_ids
+        .into_iter()
+        .min_by_key(|team_id| players_on_team(room_id, *team_id))
+        .unwrap_or(NO_TEAM)
+}
+
+pub fn players_on_team(room_id: u64, team_id: u64) -> usize {
+    StdbPlayer::filter_by_room_id(&room_id)
+        .filter(|player| player.team_id == team_id)
+        .count()
+}
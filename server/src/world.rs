@@ -0,0 +1,177 @@
+use log::info;
+use spacetimedb::{spacetimedb, Timestamp};
+
+use crate::{rooms, StdbObject, StdbVector2};
+
+pub const OBJECT_KIND_ROCK: &str = "rock";
+pub const OBJECT_KIND_TREE: &str = "tree";
+
+/// Moves further apart than this don't count towards an object's velocity, it stopped in
+/// between.
+pub const MAX_MOVE_GAP_MICROS: u64 = 500_000;
+
+/// `world_id` of the only `StdbWorld` row.
+pub const WORLD_ID: u32 = 0;
+
+/// Singleton describing the playable area, objects are kept between `min` and `max`.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbWorld {
+    #[primarykey]
+    pub world_id: u32,
+    pub min: StdbVector2,
+    pub max: StdbVector2,
+}
+
+impl StdbWorld {
+    // Returns `position` moved inside `min` and `max`.
+    pub fn clamp(&self, position: StdbVector2) -> StdbVector2 {
+        StdbVector2 {
+            x: position.x.clamp(self.min.x, self.max.x),
+            y: position.y.clamp(self.min.y, self.max.y),
+        }
+    }
+}
+
+// The playable area the world is seeded with.
+pub fn seeded_world() -> StdbWorld {
+    StdbWorld {
+        world_id: WORLD_ID,
+        min: StdbVector2 {
+            x: -1000.0,
+            y: -750.0,
+        },
+        max: StdbVector2 {
+            x: 1000.0,
+            y: 750.0,
+        },
+    }
+}
+
+// Inserts the `StdbWorld` singleton.
+pub fn seed_world() {
+    StdbWorld::insert(seeded_world()).expect("Failed to insert World.");
+    info!("Seeded World");
+}
+
+// Returns a position inside the world bounds picked from `seed`, the same seed always gives
+// the same position.
+pub fn scatter_position(seed: u64) -> StdbVector2 {
+    let Some(world) = StdbWorld::filter_by_world_id(&WORLD_ID) else {
+        return StdbVector2::default();
+    };
+
+    let z = scramble(seed);
+    let x = (z & 0xFFFF_FFFF) as f32 / u32::MAX as f32;
+    let y = (z >> 32) as f32 / u32::MAX as f32;
+    StdbVector2 {
+        x: world.min.x + x * (world.max.x - world.min.x),
+        y: world.min.y + y * (world.max.y - world.min.y),
+    }
+}
+
+// splitmix64, good enough to spread things around without a random number generator. The
+// same seed always gives the same number.
+pub fn scramble(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Returns `position` moved inside the world bounds.
+pub fn clamp_to_bounds(position: StdbVector2) -> StdbVector2 {
+    match StdbWorld::filter_by_world_id(&WORLD_ID) {
+        Some(world) => world.clamp(position),
+        None => position,
+    }
+}
+
+// Inserts the static scenery every world starts with.
+pub fn seed_props() {
+    let props = [
+        (
+            OBJECT_KIND_TREE,
+            StdbVector2 {
+                x: -350.0,
+                y: 200.0,
+            },
+        ),
+        (
+            OBJECT_KIND_TREE,
+            StdbVector2 {
+                x: -300.0,
+                y: 260.0,
+            },
+        ),
+        (OBJECT_KIND_TREE, StdbVector2 { x: 400.0, y: 250.0 }),
+        (
+            OBJECT_KIND_ROCK,
+            StdbVector2 {
+                x: 200.0,
+                y: -300.0,
+            },
+        ),
+        (
+            OBJECT_KIND_ROCK,
+            StdbVector2 {
+                x: -100.0,
+                y: -180.0,
+            },
+        ),
+    ];
+
+    // The lobby's grid is built once everything is seeded.
+    for (kind, position) in props {
+        insert_prop(kind, position, rooms::LOBBY_ROOM_ID);
+    }
+    info!("Seeded Props");
+}
+
+// Creates a prop of `kind` at `position` and returns its `object_id`. Solid props block the
+// room's nav grid, which the caller rebuilds.
+pub fn insert_prop(kind: &str, position: StdbVector2, room_id: u64) -> u64 {
+    StdbObject::insert(StdbObject {
+        object_id: 0,
+        name: kind.to_string(),
+        kind: kind.to_string(),
+        room_id,
+        position,
+        ..Default::default()
+    })
+    .expect("Failed to create a unique Prop.")
+    .object_id
+}
+
+// Moves `object` to `position`, working out its velocity from how far and how long ago it
+// last moved. Its facing only changes when it actually goes somewhere.
+pub fn move_object(object: &mut StdbObject, position: StdbVector2, now: Timestamp) {
+    let now = now.into_micros_since_epoch();
+    let elapsed = now.saturating_sub(object.moved_at_micros);
+    let (dx, dy) = (
+        position.x - object.position.x,
+        position.y - object.position.y,
+    );
+
+    if object.moved_at_micros == 0 || elapsed > MAX_MOVE_GAP_MICROS {
+        object.velocity = StdbVector2::default();
+    } else if elapsed > 0 {
+        let secs = elapsed as f32 / 1_000_000.0;
+        object.velocity = StdbVector2 {
+            x: dx / secs,
+            y: dy / secs,
+        };
+    }
+    if dx != 0.0 || dy != 0.0 {
+        object.facing = dy.atan2(dx);
+    }
+    object.position = position;
+    object.moved_at_micros = now;
+}
+
+// Puts `object` at `position` without it counting as a move, so it doesn't slide afterwards.
+pub fn place_object(object: &mut StdbObject, position: StdbVector2, now: Timestamp) {
+    object.position = position;
+    object.velocity = StdbVector2::default();
+    object.moved_at_micros = now.into_micros_since_epoch();
+}
@@ -0,0 +1,157 @@
+use log::info;
+use spacetimedb::{spacetimedb, Timestamp};
+
+use crate::{error::GameError, world::WORLD_ID};
+
+/// How long a day lasts until an admin changes it, in seconds.
+pub const DEFAULT_DAY_LENGTH_SECS: u64 = 600;
+/// Days shorter than this would flicker between day and night.
+pub const MIN_DAY_LENGTH_SECS: u64 = 30;
+/// Longest day an admin can set, a real day.
+pub const MAX_DAY_LENGTH_SECS: u64 = 24 * 60 * 60;
+
+/// Singleton with the time of day everyone shares, `world_time_id` is always `WORLD_ID`.
+/// Clients work out the time of day from it and the server's clock, so it only changes when
+/// a new day starts or the day length does.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbWorldTime {
+    #[primarykey]
+    pub world_time_id: u32,
+    // When day 0 began, in microseconds since the unix epoch.
+    pub epoch_micros: u64,
+    pub day_length_micros: u64,
+    // How many days have passed since day 0.
+    pub day: u64,
+}
+
+impl StdbWorldTime {
+    // How far through the current day `now_micros` is, 0 at midnight and 0.5 at noon.
+    pub fn time_of_day(&self, now_micros: u64) -> f32 {
+        let elapsed = now_micros.saturating_sub(self.epoch_micros);
+        let day_length = self.day_length_micros.max(1);
+        (elapsed % day_length) as f32 / day_length as f32
+    }
+
+    pub fn day_at(&self, now_micros: u64) -> u64 {
+        now_micros.saturating_sub(self.epoch_micros) / self.day_length_micros.max(1)
+    }
+
+    // Changes how long a day lasts while keeping the day and time of day it is at `now_micros`.
+    fn set_day_length(&mut self, day_length_micros: u64, now_micros: u64) {
+        let days =
+            now_micros.saturating_sub(self.epoch_micros) as f64 / self.day_length_micros as f64;
+        let elapsed = (days * day_length_micros as f64) as u64;
+        self.epoch_micros = now_micros.saturating_sub(elapsed);
+        self.day_length_micros = day_length_micros;
+    }
+}
+
+// Night is the quarter of the day either side of midnight.
+pub fn is_night(time_of_day: f32) -> bool {
+    !(0.25..0.75).contains(&time_of_day)
+}
+
+// Inserts the `StdbWorldTime` singleton, starting the first day in the morning.
+pub fn seed_world_time(now: Timestamp) {
+    let day_length_micros = DEFAULT_DAY_LENGTH_SECS * 1_000_000;
+    StdbWorldTime::insert(StdbWorldTime {
+        world_time_id: WORLD_ID,
+        epoch_micros: now
+            .into_micros_since_epoch()
+            .saturating_sub(day_length_micros / 4),
+        day_length_micros,
+        day: 0,
+    })
+    .expect("Failed to insert World Time.");
+    info!("Seeded World Time");
+}
+
+// Whether it's night in the world at `now`, for gameplay that depends on the time of day.
+pub fn is_night_at(now: Timestamp) -> bool {
+    StdbWorldTime::filter_by_world_time_id(&WORLD_ID)
+        .is_some_and(|time| is_night(time.time_of_day(now.into_micros_since_epoch())))
+}
+
+// Starts the next day once the current one is over.
+pub fn advance_world_time(now: Timestamp) {
+    let Some(mut time) = StdbWorldTime::filter_by_world_time_id(&WORLD_ID) else {
+        return;
+    };
+
+    let day = time.day_at(now.into_micros_since_epoch());
+    if day != time.day {
+        time.day = day;
+        info!("Day {} started", day);
+        StdbWorldTime::update_by_world_time_id(&WORLD_ID, time);
+    }
+}
+
+// Called by `admin::set_day_length`.
+pub fn set_day_length(secs: u64, now: Timestamp) -> Result<(), GameError> {
+    if !(MIN_DAY_LENGTH_SECS..=MAX_DAY_LENGTH_SECS).contains(&secs) {
+        return Err(GameError::Invalid(format!(
+            "A day has to last between {} and {} seconds",
+            MIN_DAY_LENGTH_SECS, MAX_DAY_LENGTH_SECS
+        )));
+    }
+    let day_length_micros = secs * 1_000_000;
+    let mut time = StdbWorldTime::filter_by_world_time_id(&WORLD_ID)
+        .ok_or(GameError::NotFound("World time"))?;
+
+    time.set_day_length(day_length_micros, now.into_micros_since_epoch());
+    StdbWorldTime::update_by_world_time_id(&WORLD_ID, time);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECS: u64 = 1_000_000;
+
+    fn world_time() -> StdbWorldTime {
+        StdbWorldTime {
+            world_time_id: WORLD_ID,
+            epoch_micros: 1_000 * SECS,
+            day_length_micros: 100 * SECS,
+            day: 0,
+        }
+    }
+
+    #[test]
+    fn days_wrap_around() {
+        let time = world_time();
+
+        assert_eq!(time.time_of_day(1_050 * SECS), 0.5);
+        assert_eq!(time.time_of_day(1_125 * SECS), 0.25);
+        assert_eq!(time.day_at(1_125 * SECS), 1);
+    }
+
+    #[test]
+    fn day_lengths_out_of_range_are_rejected() {
+        let now = Timestamp::from_micros_since_epoch(0);
+
+        assert!(set_day_length(0, now).is_err());
+        assert!(set_day_length(MAX_DAY_LENGTH_SECS + 1, now).is_err());
+        assert!(set_day_length(u64::MAX, now).is_err());
+    }
+
+    #[test]
+    fn nights_are_around_midnight() {
+        assert!(is_night(0.0));
+        assert!(is_night(0.9));
+        assert!(!is_night(0.5));
+    }
+
+    #[test]
+    fn changing_the_day_length_keeps_the_time_of_day() {
+        let mut time = world_time();
+        let now = 1_130 * SECS;
+
+        time.set_day_length(40 * SECS, now);
+
+        assert!((time.time_of_day(now) - 0.3).abs() < 0.001);
+        assert_eq!(time.day_at(now), 1);
+    }
+}
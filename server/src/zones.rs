@@ -0,0 +1,252 @@
+use log::info;
+use spacetimedb::{spacetimedb, Identity, SpacetimeType, Timestamp};
+
+use crate::{rooms, StdbObject, StdbVector2};
+
+/// How long a zone event stays in the table before `expire_zone_events` removes it.
+pub const ZONE_EVENT_LIFETIME_MICROS: u64 = 10_000_000;
+
+#[derive(SpacetimeType, Clone, Copy, PartialEq, Debug)]
+pub enum StdbZoneShape {
+    // Everything within `half_size` of `center` on both axes.
+    Rect,
+    // Everything within `radius` of `center`.
+    Circle,
+}
+
+#[derive(SpacetimeType, Clone, Copy, PartialEq, Debug)]
+pub enum StdbZoneEventKind {
+    Enter,
+    Exit,
+}
+
+/// A named area of a room, players inside it are tracked in `StdbZoneOccupant`.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbZone {
+    #[primarykey]
+    #[autoinc]
+    pub zone_id: u64,
+    pub name: String,
+    pub room_id: u64,
+    pub shape: StdbZoneShape,
+    pub center: StdbVector2,
+    // Only used by `StdbZoneShape::Rect`.
+    pub half_size: StdbVector2,
+    // Only used by `StdbZoneShape::Circle`.
+    pub radius: f32,
+    // Nothing may hurt a player inside a safe zone.
+    pub safe: bool,
+    // Scales how fast players walk inside the zone.
+    pub speed_multiplier: f32,
+}
+
+/// A player that's inside a zone right now.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbZoneOccupant {
+    #[primarykey]
+    #[autoinc]
+    pub occupant_id: u64,
+    pub client_id: Identity,
+    pub zone_id: u64,
+}
+
+/// Written whenever a player enters or leaves a zone, clients subscribe to these to react
+/// to them. Removed by `expire_zone_events` after `ZONE_EVENT_LIFETIME_MICROS`.
+#[spacetimedb(table)]
+#[derive(Clone)]
+pub struct StdbZoneEvent {
+    #[primarykey]
+    #[autoinc]
+    pub event_id: u64,
+    pub zone_id: u64,
+    pub client_id: Identity,
+    pub kind: StdbZoneEventKind,
+    pub created_at: Timestamp,
+}
+
+impl StdbZone {
+    pub fn contains(&self, room_id: u64, position: &StdbVector2) -> bool {
+        if room_id != self.room_id {
+            return false;
+        }
+
+        match self.shape {
+            StdbZoneShape::Rect => {
+                (position.x - self.center.x).abs() <= self.half_size.x
+                    && (position.y - self.center.y).abs() <= self.half_size.y
+            }
+            StdbZoneShape::Circle => position.distance(&self.center) <= self.radius,
+        }
+    }
+}
+
+// Inserts the zones of the lobby.
+pub fn seed_zones() {
+    let zones = [
+        StdbZone {
+            zone_id: 0,
+            name: "Spawn".to_string(),
+            room_id: rooms::LOBBY_ROOM_ID,
+            shape: StdbZoneShape::Circle,
+            center: StdbVector2::default(),
+            half_size: StdbVector2::default(),
+            radius: 120.0,
+            safe: true,
+            speed_multiplier: 1.0,
+        },
+        StdbZone {
+            zone_id: 0,
+            name: "Swamp".to_string(),
+            room_id: rooms::LOBBY_ROOM_ID,
+            shape: StdbZoneShape::Rect,
+            center: StdbVector2 {
+                x: -650.0,
+                y: -500.0,
+            },
+            half_size: StdbVector2 { x: 250.0, y: 180.0 },
+            radius: 0.0,
+            safe: false,
+            speed_multiplier: 0.5,
+        },
+        StdbZone {
+            zone_id: 0,
+            name: "Road".to_string(),
+            room_id: rooms::LOBBY_ROOM_ID,
+            shape: StdbZoneShape::Rect,
+            center: StdbVector2 { x: 0.0, y: 600.0 },
+            half_size: StdbVector2 { x: 900.0, y: 40.0 },
+            radius: 0.0,
+            safe: false,
+            speed_multiplier: 1.5,
+        },
+    ];
+    for zone in zones {
+        StdbZone::insert(zone).expect("Failed to insert Zone.");
+    }
+    info!("Seeded Zones");
+}
+
+// Compares the zones the player's `object` is in with the ones they were in, and writes an
+// event for every zone they entered or left. Called whenever a player's object moves.
+pub fn refresh_occupancy(client_id: Identity, object: &StdbObject, now: Timestamp) {
+    let inside: Vec<u64> = StdbZone::iter()
+        .filter(|zone| zone.contains(object.room_id, &object.position))
+        .map(|zone| zone.zone_id)
+        .collect();
+    let occupied: Vec<StdbZoneOccupant> =
+        StdbZoneOccupant::filter_by_client_id(&client_id).collect();
+
+    for occupant in &occupied {
+        if !inside.contains(&occupant.zone_id) {
+            StdbZoneOccupant::delete_by_occupant_id(&occupant.occupant_id);
+            write_event(occupant.zone_id, client_id, StdbZoneEventKind::Exit, now);
+        }
+    }
+    for zone_id in inside {
+        if !occupied.iter().any(|occupant| occupant.zone_id == zone_id) {
+            StdbZoneOccupant::insert(StdbZoneOccupant {
+                occupant_id: 0,
+                client_id,
+                zone_id,
+            })
+            .expect("Failed to insert Zone Occupant.");
+            write_event(zone_id, client_id, StdbZoneEventKind::Enter, now);
+        }
+    }
+}
+
+// Forgets which zones the client's player was in, once the player is gone. No exit events
+// are written, leaving the game isn't leaving a zone.
+pub fn forget_client(client_id: Identity) {
+    let occupied: Vec<u64> = StdbZoneOccupant::filter_by_client_id(&client_id)
+        .map(|occupant| occupant.occupant_id)
+        .collect();
+    for occupant_id in occupied {
+        StdbZoneOccupant::delete_by_occupant_id(&occupant_id);
+    }
+}
+
+// How much faster or slower the client's player walks in the zones they're in.
+pub fn speed_multiplier(client_id: Identity) -> f32 {
+    StdbZoneOccupant::filter_by_client_id(&client_id)
+        .filter_map(|occupant| StdbZone::filter_by_zone_id(&occupant.zone_id))
+        .map(|zone| zone.speed_multiplier)
+        .product()
+}
+
+// Removes every zone event older than `ZONE_EVENT_LIFETIME_MICROS`.
+pub fn expire_zone_events(now: Timestamp) {
+    let expired: Vec<u64> = StdbZoneEvent::iter()
+        .filter(|event| {
+            now.duration_since(event.created_at).map_or(false, |age| {
+                age.as_micros() >= ZONE_EVENT_LIFETIME_MICROS as u128
+            })
+        })
+        .map(|event| event.event_id)
+        .collect();
+
+    for event_id in expired {
+        StdbZoneEvent::delete_by_event_id(&event_id);
+    }
+}
+
+fn write_event(zone_id: u64, client_id: Identity, kind: StdbZoneEventKind, now: Timestamp) {
+    StdbZoneEvent::insert(StdbZoneEvent {
+        event_id: 0,
+        zone_id,
+        client_id,
+        kind,
+        created_at: now,
+    })
+    .expect("Failed to insert Zone Event.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(shape: StdbZoneShape) -> StdbZone {
+        StdbZone {
+            zone_id: 1,
+            name: "Zone".to_string(),
+            room_id: 0,
+            shape,
+            center: StdbVector2 { x: 10.0, y: 10.0 },
+            half_size: StdbVector2 { x: 5.0, y: 2.0 },
+            radius: 5.0,
+            safe: false,
+            speed_multiplier: 1.0,
+        }
+    }
+
+    fn at(x: f32, y: f32) -> StdbVector2 {
+        StdbVector2 { x, y }
+    }
+
+    #[test]
+    fn rect_zones_contain_their_edges() {
+        let zone = zone(StdbZoneShape::Rect);
+
+        assert!(zone.contains(0, &at(15.0, 12.0)));
+        assert!(zone.contains(0, &at(5.0, 8.0)));
+        assert!(!zone.contains(0, &at(10.0, 13.0)));
+    }
+
+    #[test]
+    fn circle_zones_are_round() {
+        let zone = zone(StdbZoneShape::Circle);
+
+        assert!(zone.contains(0, &at(10.0, 15.0)));
+        // Inside the bounding box, but past the radius.
+        assert!(!zone.contains(0, &at(14.0, 14.0)));
+    }
+
+    #[test]
+    fn zones_only_contain_positions_in_their_room() {
+        let zone = zone(StdbZoneShape::Circle);
+
+        assert!(!zone.contains(1, &at(10.0, 10.0)));
+    }
+}